@@ -0,0 +1,251 @@
+use crate::{FrameSequenceParser, Rule};
+use pest::Parser;
+
+/// Whether `rule` is a sequence, or a part of one, of any [`Dialect`] that
+/// only contains other parts.
+pub(crate) fn is_sequence_rule(rule: Rule) -> bool {
+    matches!(rule, Rule::FrameSequence | Rule::FrameSequencePart)
+        || Dialect::ALL.iter().any(|dialect| dialect.rule() == rule)
+}
+
+/// Whether `rule` is a whole string in a [`Dialect`] other than
+/// [`Dialect::Native`]. The parts of these are silent.
+pub(crate) fn is_dialect_sequence_rule(rule: Rule) -> bool {
+    Dialect::ALL[1..]
+        .iter()
+        .any(|dialect| dialect.rule() == rule)
+}
+
+/// Whether `rule` is a frame range of any [`Dialect`].
+pub(crate) fn is_range_rule(rule: Rule) -> bool {
+    Dialect::ALL
+        .iter()
+        .any(|dialect| dialect.range_rule() == rule)
+}
+
+/// The syntax a frame sequence string is written in.
+///
+/// Used with [`parse_frame_sequence_with()`](crate::parse_frame_sequence_with).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Dialect {
+    /// This crate's own syntax, e.g. `1-100@5,120`.
+    ///
+    /// See the main page of the documentation for details.
+    #[default]
+    Native,
+    /// Nuke, e.g. `1-100x5 120`.
+    ///
+    /// Parts are separated by spaces or commas.
+    Nuke,
+    /// Houdini, e.g. `1 100 5`.
+    ///
+    /// Start, end and an optional step size, separated by spaces.
+    Houdini,
+    /// Maya, e.g. `1:100:5`.
+    ///
+    /// Start, end and an optional step size, separated by colons.
+    Maya,
+    /// Deadline, e.g. `1-100x5,120`.
+    ///
+    /// Parts are separated by commas or spaces.
+    Deadline,
+    /// RV, e.g. `1-100x5#`.
+    ///
+    /// Trailing `#` or `@` padding hints are ignored.
+    Rv,
+    /// Katana, e.g. `1-100/5,120`.
+    Katana,
+}
+
+impl Dialect {
+    /// All dialects, in declaration order.
+    pub const ALL: [Dialect; 7] = [
+        Dialect::Native,
+        Dialect::Nuke,
+        Dialect::Houdini,
+        Dialect::Maya,
+        Dialect::Deadline,
+        Dialect::Rv,
+        Dialect::Katana,
+    ];
+
+    /// The grammar rule that parses a whole string in this dialect.
+    pub(crate) fn rule(self) -> Rule {
+        match self {
+            Dialect::Native => Rule::FrameSequenceString,
+            Dialect::Nuke => Rule::NukeFrameSequenceString,
+            Dialect::Houdini => Rule::HoudiniFrameSequenceString,
+            Dialect::Maya => Rule::MayaFrameSequenceString,
+            Dialect::Deadline => Rule::DeadlineFrameSequenceString,
+            Dialect::Rv => Rule::RvFrameSequenceString,
+            Dialect::Katana => Rule::KatanaFrameSequenceString,
+        }
+    }
+
+    /// The grammar rule of a frame range in this dialect.
+    fn range_rule(self) -> Rule {
+        match self {
+            Dialect::Native => Rule::FrameRange,
+            Dialect::Nuke => Rule::NukeFrameRange,
+            Dialect::Houdini => Rule::HoudiniFrameRange,
+            Dialect::Maya => Rule::MayaFrameRange,
+            Dialect::Deadline => Rule::DeadlineFrameRange,
+            Dialect::Rv => Rule::RvFrameRange,
+            Dialect::Katana => Rule::KatanaFrameRange,
+        }
+    }
+
+    /// How strongly `input` hints at this dialect, beyond merely being
+    /// valid syntax for it.
+    fn evidence(self, input: &str) -> f32 {
+        let spaced = input.trim().contains(' ');
+        match self {
+            Dialect::Native => {
+                if input.contains(['@', '!', '^']) || input.contains("..") {
+                    2.0
+                } else {
+                    0.5
+                }
+            }
+            Dialect::Nuke if spaced => 1.0,
+            Dialect::Houdini if spaced => 1.5,
+            Dialect::Maya if input.contains(':') => 1.0,
+            Dialect::Deadline if input.contains('x') && input.contains(',') => 0.75,
+            Dialect::Rv if input.contains('#') => 2.0,
+            Dialect::Katana if input.contains('/') => 2.0,
+            _ => 0.0,
+        }
+    }
+}
+
+/// The result of [`detect_dialect()`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct DialectDetection {
+    /// The most likely dialect.
+    pub dialect: Dialect,
+    /// How likely [`dialect`](Self::dialect) is, in the range `(0, 1]`.
+    pub confidence: f32,
+    /// Other dialects the input is valid in, most likely first.
+    ///
+    /// Empty if the input is only valid in [`dialect`](Self::dialect).
+    pub alternatives: Vec<(Dialect, f32)>,
+}
+
+/// Guess the [`Dialect`] a frame sequence string was written in.
+///
+/// Returns [`None`] if `input` is not valid in any dialect.
+///
+/// The confidences of the detected dialect and all alternatives add up to
+/// one. Note that alternatives often describe the same frames, e.g. `1-10`
+/// is valid in all but the Houdini and Maya dialects.
+///
+/// ```
+/// # use frame_sequence::{detect_dialect, Dialect};
+/// assert_eq!(Dialect::Houdini, detect_dialect("1 100 5").unwrap().dialect);
+/// assert_eq!(Dialect::Katana, detect_dialect("1-100/5").unwrap().dialect);
+/// ```
+pub fn detect_dialect(input: &str) -> Option<DialectDetection> {
+    let mut candidates = Dialect::ALL
+        .into_iter()
+        .filter(|dialect| FrameSequenceParser::parse(dialect.rule(), input).is_ok())
+        .map(|dialect| (dialect, 1.0 + dialect.evidence(input)))
+        .collect::<Vec<_>>();
+
+    let total = candidates.iter().map(|(_, score)| score).sum::<f32>();
+    candidates.iter_mut().for_each(|(_, score)| *score /= total);
+    // Stable, so ties are resolved in declaration order.
+    candidates.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    let mut candidates = candidates.into_iter();
+    candidates
+        .next()
+        .map(|(dialect, confidence)| DialectDetection {
+            dialect,
+            confidence,
+            alternatives: candidates.collect(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{detect_dialect, parse_frame_sequence_with, Dialect};
+
+    #[test]
+    fn test_dialects() {
+        let expected = [1, 6, 11, 16, 20];
+        for (input, dialect) in [
+            ("1-16@5,20", Dialect::Native),
+            ("1-16x5 20", Dialect::Nuke),
+            ("1-16x5,20", Dialect::Deadline),
+            ("1-16x5####,20#", Dialect::Rv),
+            ("1-16/5,20", Dialect::Katana),
+        ] {
+            assert_eq!(
+                expected,
+                parse_frame_sequence_with(input, dialect)
+                    .unwrap()
+                    .as_slice(),
+                "{dialect:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_houdini_and_maya() {
+        let expected = [-5, 0, 5, 10];
+        assert_eq!(
+            expected,
+            parse_frame_sequence_with(" -5 10  5", Dialect::Houdini)
+                .unwrap()
+                .as_slice()
+        );
+        assert_eq!(
+            expected,
+            parse_frame_sequence_with("-5:10:5", Dialect::Maya)
+                .unwrap()
+                .as_slice()
+        );
+        assert_eq!(
+            [3, 2, 1],
+            parse_frame_sequence_with("3 1", Dialect::Houdini)
+                .unwrap()
+                .as_slice()
+        );
+    }
+
+    #[test]
+    fn test_dialect_mismatch() {
+        assert!(parse_frame_sequence_with("1-100@5", Dialect::Katana).is_err());
+        assert!(parse_frame_sequence_with("1 100 5", Dialect::Native).is_err());
+    }
+
+    #[test]
+    fn test_detect_dialect() {
+        for (input, dialect) in [
+            ("1-100@5,120", Dialect::Native),
+            ("-10..-5", Dialect::Native),
+            ("1-100x5 120", Dialect::Nuke),
+            ("1 100 5", Dialect::Houdini),
+            ("1:100:5", Dialect::Maya),
+            ("1-100x5,120", Dialect::Deadline),
+            ("1-100x5#", Dialect::Rv),
+            ("1-100/5", Dialect::Katana),
+        ] {
+            assert_eq!(dialect, detect_dialect(input).unwrap().dialect, "{input}");
+        }
+    }
+
+    #[test]
+    fn test_detect_dialect_confidence() {
+        let detection = detect_dialect("1-100x5#").unwrap();
+        assert_eq!(1.0, detection.confidence);
+        assert!(detection.alternatives.is_empty());
+
+        let detection = detect_dialect("1-10").unwrap();
+        assert_eq!(Dialect::Native, detection.dialect);
+        assert_eq!(4, detection.alternatives.len());
+        assert!(detection.alternatives[0].1 <= detection.confidence);
+
+        assert!(detect_dialect("1-10@@x").is_none());
+    }
+}
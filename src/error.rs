@@ -0,0 +1,267 @@
+use crate::Rule;
+use pest::error::{Error, ErrorVariant, InputLocation};
+use std::{fmt, ops::Range};
+
+/// An error returned when parsing a frame sequence string.
+///
+/// The variant tells what went wrong, the [`ErrorDetails`] where and how to
+/// fix it:
+///
+/// ```
+/// # use frame_sequence::{parse_frame_sequence, FrameSequenceError};
+/// let error = parse_frame_sequence("1-10,20-30@0").unwrap_err();
+/// assert!(matches!(error, FrameSequenceError::ZeroStep(_)));
+/// assert_eq!(11..12, error.span());
+/// assert!(error.suggestion().is_some());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum FrameSequenceError {
+    /// The input is not valid syntax, e.g. `1-10@`.
+    Syntax(Box<ErrorDetails>),
+    /// Three or more numbers joined by dashes, e.g. `1-2-3`.
+    AmbiguousRange(Box<ErrorDetails>),
+    /// A step size of zero or less, e.g. `1-10@0`.
+    ZeroStep(Box<ErrorDetails>),
+    /// A frame count or hold of zero or less, e.g. `1-10#0`.
+    ZeroCount(Box<ErrorDetails>),
+    /// A division by zero, e.g. `10/0`.
+    DivisionByZero(Box<ErrorDetails>),
+    /// A number, frame or seed that does not fit its type.
+    Overflow(Box<ErrorDetails>),
+    /// A symbol or open-ended range without a value in the
+    /// [`FrameContext`](crate::FrameContext), e.g. `current`.
+    UnresolvedSymbol(Box<ErrorDetails>),
+    /// One of the [`Limits`](crate::Limits) or a fixed limit, e.g. the
+    /// maximum nesting depth of groups, was exceeded.
+    LimitExceeded(Box<ErrorDetails>),
+}
+
+/// Where an error occurred and how to fix it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ErrorDetails {
+    span: Range<usize>,
+    suggestion: Option<String>,
+    error: Error<Rule>,
+}
+
+impl ErrorDetails {
+    /// The byte range of the offending part of the input. Empty if the error
+    /// is at a single position, e.g. an unexpected end of the input.
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+
+    /// What went wrong, without the location.
+    pub fn message(&self) -> String {
+        self.error.variant.message().into_owned()
+    }
+
+    /// A human readable hint how to fix the input, if there is one.
+    pub fn suggestion(&self) -> Option<&str> {
+        self.suggestion.as_deref()
+    }
+
+    /// The underlying [`pest`] error.
+    pub fn pest_error(&self) -> &Error<Rule> {
+        &self.error
+    }
+}
+
+/// A [`FrameSequenceError`] variant.
+pub(crate) type ErrorKind = fn(Box<ErrorDetails>) -> FrameSequenceError;
+
+impl FrameSequenceError {
+    pub(crate) fn new(kind: ErrorKind, error: Error<Rule>) -> Self {
+        let span = match error.location {
+            InputLocation::Pos(position) => position..position,
+            InputLocation::Span((start, end)) => start..end,
+        };
+        kind(Box::new(ErrorDetails {
+            span,
+            suggestion: None,
+            error,
+        }))
+    }
+
+    pub(crate) fn kind(&self) -> ErrorKind {
+        match self {
+            FrameSequenceError::Syntax(_) => FrameSequenceError::Syntax,
+            FrameSequenceError::AmbiguousRange(_) => FrameSequenceError::AmbiguousRange,
+            FrameSequenceError::ZeroStep(_) => FrameSequenceError::ZeroStep,
+            FrameSequenceError::ZeroCount(_) => FrameSequenceError::ZeroCount,
+            FrameSequenceError::DivisionByZero(_) => FrameSequenceError::DivisionByZero,
+            FrameSequenceError::Overflow(_) => FrameSequenceError::Overflow,
+            FrameSequenceError::UnresolvedSymbol(_) => FrameSequenceError::UnresolvedSymbol,
+            FrameSequenceError::LimitExceeded(_) => FrameSequenceError::LimitExceeded,
+        }
+    }
+
+    pub(crate) fn with_suggestion(mut self, suggestion: impl Into<String>) -> Self {
+        self.details_mut().suggestion = Some(suggestion.into());
+        self
+    }
+
+    /// Where the error occurred and how to fix it.
+    pub fn details(&self) -> &ErrorDetails {
+        match self {
+            FrameSequenceError::Syntax(details)
+            | FrameSequenceError::AmbiguousRange(details)
+            | FrameSequenceError::ZeroStep(details)
+            | FrameSequenceError::ZeroCount(details)
+            | FrameSequenceError::DivisionByZero(details)
+            | FrameSequenceError::Overflow(details)
+            | FrameSequenceError::UnresolvedSymbol(details)
+            | FrameSequenceError::LimitExceeded(details) => details,
+        }
+    }
+
+    fn details_mut(&mut self) -> &mut ErrorDetails {
+        match self {
+            FrameSequenceError::Syntax(details)
+            | FrameSequenceError::AmbiguousRange(details)
+            | FrameSequenceError::ZeroStep(details)
+            | FrameSequenceError::ZeroCount(details)
+            | FrameSequenceError::DivisionByZero(details)
+            | FrameSequenceError::Overflow(details)
+            | FrameSequenceError::UnresolvedSymbol(details)
+            | FrameSequenceError::LimitExceeded(details) => details,
+        }
+    }
+
+    /// See [`ErrorDetails::span()`].
+    pub fn span(&self) -> Range<usize> {
+        self.details().span()
+    }
+
+    /// See [`ErrorDetails::message()`].
+    pub fn message(&self) -> String {
+        self.details().message()
+    }
+
+    /// See [`ErrorDetails::suggestion()`].
+    pub fn suggestion(&self) -> Option<&str> {
+        self.details().suggestion()
+    }
+
+    /// See [`ErrorDetails::pest_error()`].
+    pub fn pest_error(&self) -> &Error<Rule> {
+        self.details().pest_error()
+    }
+
+    /// Converts this into the underlying [`pest`] error.
+    pub fn into_pest_error(self) -> Error<Rule> {
+        match self {
+            FrameSequenceError::Syntax(details)
+            | FrameSequenceError::AmbiguousRange(details)
+            | FrameSequenceError::ZeroStep(details)
+            | FrameSequenceError::ZeroCount(details)
+            | FrameSequenceError::DivisionByZero(details)
+            | FrameSequenceError::Overflow(details)
+            | FrameSequenceError::UnresolvedSymbol(details)
+            | FrameSequenceError::LimitExceeded(details) => details.error,
+        }
+    }
+}
+
+impl From<Error<Rule>> for FrameSequenceError {
+    /// A syntax error, with the grammar rules the parser expected replaced
+    /// by human readable names.
+    fn from(error: Error<Rule>) -> Self {
+        let error = match error.variant {
+            ErrorVariant::ParsingError { .. } => error.renamed_rules(rule_name),
+            ErrorVariant::CustomError { .. } => error,
+        };
+        FrameSequenceError::new(FrameSequenceError::Syntax, error)
+    }
+}
+
+fn rule_name(rule: &Rule) -> String {
+    match rule {
+        Rule::FrameSequencePart | Rule::SubframeSequencePart => "a frame, range or group",
+        Rule::Exclusion | Rule::SubframeExclusion => "an exclusion",
+        Rule::PositiveNumber
+        | Rule::Frame
+        | Rule::Factor
+        | Rule::Product
+        | Rule::Expression
+        | Rule::RangeStart
+        | Rule::RangeEnd
+        | Rule::PositiveSubframe
+        | Rule::Subframe => "a number",
+        Rule::FrameSymbol => "`first`, `last` or `current`",
+        Rule::StepSymbol => "a step size",
+        Rule::BinarySequenceSymbol => "`b`",
+        Rule::RandomSymbol | Rule::ReverseSymbol => "`r`",
+        Rule::PingPongSymbol => "`pp`",
+        Rule::IncludeLastSymbol => "`e`",
+        Rule::PassList => "a pass list",
+        Rule::FrameCount => "a frame count",
+        Rule::Sign | Rule::Shift => "a shift",
+        Rule::Hold => "a hold",
+        Rule::AddOperator => "`+` or `-`",
+        Rule::MulOperator => "`*` or `/`",
+        Rule::EOI => "the end of the input",
+        rule => return format!("{rule:?}"),
+    }
+    .to_string()
+}
+
+impl fmt::Display for FrameSequenceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.pest_error())?;
+        if let Some(suggestion) = self.suggestion() {
+            write!(f, "\n  = help: {suggestion}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for FrameSequenceError {}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse_frame_sequence, parse_subframe_sequence, FrameSequenceError};
+
+    #[test]
+    fn test_error_kinds() {
+        use FrameSequenceError::*;
+        let error = |input| parse_frame_sequence(input).unwrap_err();
+        assert!(matches!(error("1-10@"), Syntax(_)));
+        assert!(matches!(error("1-2-3"), AmbiguousRange(_)));
+        assert!(matches!(error("1-10@0"), ZeroStep(_)));
+        assert!(matches!(error("1-10#0"), ZeroCount(_)));
+        assert!(matches!(error("1-10*0"), ZeroCount(_)));
+        assert!(matches!(error("10/0"), DivisionByZero(_)));
+        assert!(matches!(error("99999999999999999999"), Overflow(_)));
+        assert!(matches!(error("current"), UnresolvedSymbol(_)));
+        assert!(matches!(error("1-"), UnresolvedSymbol(_)));
+        assert!(matches!(error("1-99999999999"), LimitExceeded(_)));
+        assert!(matches!(
+            parse_subframe_sequence("1-2@0.0").unwrap_err(),
+            ZeroStep(_)
+        ));
+    }
+
+    #[test]
+    fn test_error_details() {
+        let error = parse_frame_sequence("1-10,20-30#0").unwrap_err();
+        assert_eq!(11..12, error.span());
+        assert_eq!("frame count must be greater than zero", error.message());
+        assert_eq!(
+            Some("use a frame count of at least `1`"),
+            error.suggestion()
+        );
+        assert!(error.to_string().contains("help: use a frame count"));
+
+        let error = parse_frame_sequence("1-10,").unwrap_err();
+        assert_eq!(5..5, error.span());
+        assert_eq!(None, error.suggestion());
+        assert!(error.message().contains("a frame, range or group"));
+        assert!(!error.message().contains("FrameSequencePart"));
+        assert_eq!(
+            pest::error::InputLocation::Pos(5),
+            error.into_pest_error().location
+        );
+    }
+}
@@ -0,0 +1,602 @@
+use itertools::Itertools;
+use std::{borrow::Cow, collections::HashSet, fmt, iter::repeat_n};
+
+/// Runs up to this long are tried with every possible end. Longer ones only
+/// with their full length or without their last frame, which is what the
+/// shortest string uses in practice.
+const MAX_RUN_CANDIDATES: usize = 32;
+
+/// Formats frames as the shortest frame sequence string that parses back
+/// into the same frames. The inverse of
+/// [`parse_frame_sequence()`](crate::parse_frame_sequence).
+///
+/// Frames with a constant step between them, forwards or backwards, become
+/// ranges:
+///
+/// `[1, 2, 3, 5, 7, 9, 20]` ⟶ `1-3,5-9@2,20`
+///
+/// `[20, 15, 10, 5]` ⟶ `20-5@5`
+///
+/// A frame repeated right after itself becomes a [hold](crate#holds), a
+/// range that goes back to its start a [ping-pong](crate#ping-pong):
+///
+/// `[1, 1, 2, 2, 7]` ⟶ `1-2*2,7`
+///
+/// `[1, 2, 3, 4, 3, 2, 1]` ⟶ `1-4@pp`
+///
+/// Other repetitions can not be expressed in a frame sequence string, as
+/// the parser removes them, and neither can an empty list. These give
+/// [`None`].
+///
+/// The string always parses back with [`Limits::NONE`](crate::Limits::NONE).
+/// The default [`Limits`](crate::Limits) reject it if it has more parts
+/// than [`max_parts`](crate::Limits::max_parts), e.g. for 10,001 scattered
+/// frames, is longer than
+/// [`max_input_length`](crate::Limits::max_input_length) or holds frames
+/// more often than [`max_frames`](crate::Limits::max_frames) allows.
+///
+/// ```
+/// # use frame_sequence::{format_frame_sequence, parse_frame_sequence};
+/// let frames = [1, 2, 3, 5, 7, 9, 20];
+/// let sequence = format_frame_sequence(&frames).unwrap();
+/// assert_eq!("1-3,5-9@2,20", sequence);
+/// assert_eq!(frames.as_slice(), parse_frame_sequence(&sequence).unwrap());
+///
+/// assert_eq!(None, format_frame_sequence(&[1, 2, 3, 1]));
+/// ```
+///
+/// Use [`format_frame_sequence_with()`] to write the string for another
+/// application.
+pub fn format_frame_sequence(frames: &[isize]) -> Option<String> {
+    format_frame_sequence_with(frames, &FormatStyle::default())
+}
+
+/// How [`format_frame_sequence_with()`] writes a frame sequence string.
+///
+/// The default style is the syntax of this crate. E.g. for Deadline:
+///
+/// ```
+/// # use frame_sequence::{
+/// #     format_frame_sequence_with, parse_frame_sequence_with, Dialect, FormatStyle,
+/// # };
+/// let style = FormatStyle {
+///     step_token: "x".into(),
+///     negative_range_separator: "-".into(),
+///     allow_reverse: false,
+///     ..Default::default()
+/// };
+/// let frames = [1, 3, 5, 7, 9, -3, -2, -1, 20, 19];
+/// let sequence = format_frame_sequence_with(&frames, &style).unwrap();
+/// assert_eq!("1-9x2,-3--1,20,19", sequence);
+/// assert_eq!(
+///     frames.as_slice(),
+///     parse_frame_sequence_with(&sequence, Dialect::Deadline).unwrap()
+/// );
+/// ```
+///
+/// The tokens can also be read from configuration at runtime, e.g.
+/// `step_token: config.step_token.into()` for a [`String`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct FormatStyle {
+    /// Between the end of a range and its step size, e.g. `@` or `x`.
+    pub step_token: Cow<'static, str>,
+    /// Between parts, e.g. `,` or ` `.
+    pub part_separator: Cow<'static, str>,
+    /// Between the start and end of a range, e.g. `-` or `:`.
+    pub range_separator: Cow<'static, str>,
+    /// Between the start and end of a range with a negative frame. This
+    /// crate parses `-` there as a sign, so the default is `..`.
+    pub negative_range_separator: Cow<'static, str>,
+    /// Write ranges with a step size other than one. If `false`, such frames
+    /// are listed one by one.
+    pub allow_steps: bool,
+    /// Write backwards ranges, e.g. `10-1`.
+    pub allow_reverse: bool,
+    /// Write frames repeated right after themselves as a
+    /// [hold](crate#holds), e.g. `1-3*2`. If `false`, each repetition is
+    /// listed, which this crate parses as a single frame.
+    pub allow_holds: bool,
+    /// Write a range that goes back to its start as a
+    /// [ping-pong](crate#ping-pong), e.g. `1-4@pp`. If `false`, its frames
+    /// can not be expressed.
+    pub allow_ping_pong: bool,
+    /// The most frames written as one range. Longer runs are split into
+    /// several ranges. `1`, without holds, writes a plain list of frames.
+    pub max_run_length: usize,
+}
+
+impl Default for FormatStyle {
+    fn default() -> Self {
+        Self {
+            step_token: "@".into(),
+            part_separator: ",".into(),
+            range_separator: "-".into(),
+            negative_range_separator: "..".into(),
+            allow_steps: true,
+            allow_reverse: true,
+            allow_holds: true,
+            allow_ping_pong: true,
+            max_run_length: usize::MAX,
+        }
+    }
+}
+
+/// Formats frames as the shortest frame sequence string in the given
+/// [`FormatStyle`].
+///
+/// See [`format_frame_sequence()`]. The string parses back into the same
+/// frames if the style is one this crate, or the
+/// [`Dialect`](crate::Dialect) it is meant for, can parse.
+///
+/// ```
+/// # use frame_sequence::{format_frame_sequence_with, FormatStyle};
+/// let style = FormatStyle {
+///     allow_holds: false,
+///     max_run_length: 1,
+///     ..Default::default()
+/// };
+/// assert_eq!(
+///     "1,2,3,3,10",
+///     format_frame_sequence_with(&[1, 2, 3, 3, 10], &style).unwrap()
+/// );
+/// ```
+pub fn format_frame_sequence_with(frames: &[isize], style: &FormatStyle) -> Option<String> {
+    let frames = frames
+        .iter()
+        .dedup_with_count()
+        .map(|(hold, frame)| (*frame, hold))
+        .collect::<Vec<_>>();
+    Some(
+        shortest_parts(&frames, style)?
+            .into_iter()
+            .map(|part| part.format(style))
+            .join(&style.part_separator),
+    )
+}
+
+/// A part of a frame sequence string: `count` frames, `step` apart, each
+/// held `hold` times, optionally played back to the start.
+#[derive(Clone, Copy, Debug)]
+struct Part {
+    start: isize,
+    step: isize,
+    count: usize,
+    hold: usize,
+    ping_pong: bool,
+}
+
+impl Part {
+    /// The part for `frames[first..=last]`, which must be a run. With
+    /// `ping_pong`, only the frames up to the turn are taken.
+    fn new(frames: &[(isize, usize)], first: usize, last: usize, ping_pong: bool) -> Self {
+        let last = if ping_pong {
+            first + (last - first) / 2
+        } else {
+            last
+        };
+        Self {
+            start: frames[first].0,
+            step: if first == last {
+                1
+            } else {
+                frames[first + 1].0 - frames[first].0
+            },
+            count: last - first + 1,
+            hold: frames[first].1,
+            ping_pong,
+        }
+    }
+
+    fn end(&self) -> isize {
+        (self.start as i128 + self.step as i128 * (self.count as i128 - 1)) as isize
+    }
+
+    fn format(&self, style: &FormatStyle) -> String {
+        let (start, end) = (Frame(self.start), Frame(self.end()));
+        if 1 == self.count && (1 == self.hold || !style.allow_holds) {
+            // Without holds, each repetition is listed.
+            return repeat_n(start.to_string(), self.hold).join(&style.part_separator);
+        }
+
+        let separator = if self.start < 0 || self.end() < 0 {
+            &style.negative_range_separator
+        } else {
+            &style.range_separator
+        };
+        let range = format!("{start}{separator}{end}");
+        let (step, step_token) = (self.step.unsigned_abs(), &style.step_token);
+        if self.ping_pong {
+            let step = if 1 < step {
+                step.to_string()
+            } else {
+                String::new()
+            };
+            let hold = if 1 < self.hold {
+                format!("*{}", self.hold)
+            } else {
+                String::new()
+            };
+            return format!("{range}{step_token}{step}pp{hold}");
+        }
+        match (1 < step, 1 < self.hold) {
+            // A step size is an expression, `@2*3` is a step of six.
+            (true, true) => format!("({range}{step_token}{step})*{}", self.hold),
+            (true, false) => format!("{range}{step_token}{step}"),
+            (false, true) => format!("{range}*{}", self.hold),
+            (false, false) => range,
+        }
+    }
+}
+
+/// A frame as it can be parsed back.
+struct Frame(isize);
+
+impl fmt::Display for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            // `9223372036854775808` does not fit an `isize`.
+            isize::MIN => write!(f, "({}*2)", isize::MIN / 2),
+            frame => write!(f, "{frame}"),
+        }
+    }
+}
+
+/// Splits `frames`, each with its hold, into the runs and ping-pongs that
+/// give the shortest string, or [`None`] if a frame is repeated elsewhere.
+fn shortest_parts(frames: &[(isize, usize)], style: &FormatStyle) -> Option<Vec<Part>> {
+    let count = frames.len();
+    if 0 == count {
+        return None;
+    }
+
+    // The step from each frame to the next, if they can be in one range.
+    let steps = frames
+        .iter()
+        .tuple_windows()
+        .map(|(frame, next)| {
+            next.0.checked_sub(frame.0).filter(|step| {
+                step.checked_abs().is_some()
+                    // A held frame is not a range, even without holds.
+                    && frame.1 == next.1
+                    && (style.allow_holds || 1 == frame.1)
+                    && (style.allow_steps || 1 == step.abs())
+                    && (style.allow_reverse || 0 < *step)
+            })
+        })
+        .collect::<Vec<_>>();
+
+    // The last frame of the longest run starting at each frame.
+    let mut run_ends = vec![0; count];
+    for index in (0..count).rev() {
+        run_ends[index] = match steps.get(index).copied().flatten() {
+            Some(step) if index + 2 < count && Some(step) == steps[index + 1] => {
+                run_ends[index + 1]
+            }
+            Some(_) => index + 1,
+            None => index,
+        };
+    }
+
+    // The last frame of the longest stretch of frames seen for the first
+    // time, starting at each frame.
+    let mut seen = HashSet::new();
+    let is_new = frames
+        .iter()
+        .map(|(frame, _)| seen.insert(*frame))
+        .collect::<Vec<_>>();
+    let mut new_ends = vec![0; count];
+    for index in (0..count).rev() {
+        new_ends[index] = match index + 1 < count && is_new[index + 1] {
+            true => new_ends[index + 1],
+            false => index,
+        };
+    }
+
+    // How far the frames after the end of a run mirror the frames before
+    // it, up to the start of the longest run that ends there.
+    let mut run_starts = vec![usize::MAX; count];
+    for first in (0..count).rev() {
+        run_starts[run_ends[first]] = first;
+    }
+    let mirrored = (0..count)
+        .map(|turn| match run_starts[turn] {
+            usize::MAX => 0,
+            start => (1..=turn - start)
+                .take_while(|offset| frames.get(turn + offset) == Some(&frames[turn - offset]))
+                .count(),
+        })
+        .collect::<Vec<_>>();
+
+    // The length of the shortest string for the frames from each index on
+    // and the last frame of its first part, if they can be expressed.
+    let max_run_length = style.max_run_length.max(1);
+    let mut shortest = vec![None; count + 1];
+    shortest[count] = Some((0, 0, false));
+    for first in (0..count).rev() {
+        if !is_new[first] {
+            continue;
+        }
+        let run_end = run_ends[first]
+            .min(new_ends[first])
+            .min(first.saturating_add(max_run_length - 1));
+        let mut candidates = if run_end - first <= MAX_RUN_CANDIDATES {
+            (first..=run_end).rev().collect::<Vec<_>>()
+        } else {
+            vec![run_end, run_end - 1, first]
+        }
+        .into_iter()
+        .map(|last| (last, false))
+        .collect::<Vec<_>>();
+        // A ping-pong can only turn at the end of the longest run, as the
+        // run would otherwise continue instead of going back.
+        let turn = run_ends[first];
+        if style.allow_ping_pong
+            && first < turn
+            && turn <= run_end
+            && turn - first <= mirrored[turn]
+        {
+            candidates.insert(0, (2 * turn - first, true));
+        }
+
+        shortest[first] = candidates
+            .into_iter()
+            .filter_map(|(last, ping_pong)| {
+                let separator = if last + 1 < count {
+                    style.part_separator.len()
+                } else {
+                    0
+                };
+                let length = Part::new(frames, first, last, ping_pong)
+                    .format(style)
+                    .len();
+                let (rest, _, _) = shortest[last + 1]?;
+                Some((length + separator + rest, last, ping_pong))
+            })
+            // The first, longest run wins a tie.
+            .min_by_key(|(length, _, _)| *length);
+    }
+
+    let mut parts = Vec::new();
+    let mut first = 0;
+    while first < count {
+        let (_, last, ping_pong) = shortest[first]?;
+        parts.push(Part::new(frames, first, last, ping_pong));
+        first = last + 1;
+    }
+    Some(parts)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        format_frame_sequence, format_frame_sequence_with, parse_frame_sequence,
+        parse_frame_sequence_with_options, random::SplitMix64, FormatStyle, FrameSequenceError,
+        Limits, ParseOptions,
+    };
+    use itertools::Itertools;
+    use std::iter::repeat_n;
+
+    fn parse(input: &str) -> Vec<isize> {
+        parse_frame_sequence(input).unwrap()
+    }
+
+    #[test]
+    fn test_format_frame_sequence() {
+        for (frames, expected) in [
+            (vec![1, 2, 3, 5, 7, 9, 20], "1-3,5-9@2,20"),
+            (vec![10, 9, 8, 7], "10-7"),
+            (vec![1, 5], "1,5"),
+            (vec![1, 11, 21], "1-21@10"),
+            (vec![10, 8, 6, 5, 4, 3], "10,8,6-3"),
+            (vec![20, 15, 10, 5], "20-5@5"),
+            (vec![1, 11, 21, 31], "1-31@10"),
+            (vec![-3, -2, -1, 0, 1], "-3..1"),
+            (vec![1, 1, 2, 2, 3, 3], "1-3*2"),
+            (vec![1, 1, 3, 3, 5, 5], "(1-5@2)*2"),
+            (vec![4, 4, 4], "4-4*3"),
+            (vec![1, 2, 3, 4, 3, 2, 1], "1-4@pp"),
+            (vec![1, 3, 5, 3, 1, 2], "1-5@2pp,2"),
+            (vec![4, 4, 5, 5, 4, 4], "4-5@pp*2"),
+            (vec![0, 10, 9, 8, 9, 10], "0,10-8@pp"),
+            (vec![1, 2, 1, 3], "1-2@pp,3"),
+            (vec![1, 2, 3, 2], "1,2-3@pp"),
+            (
+                vec![isize::MIN, isize::MAX],
+                "(-4611686018427387904*2),9223372036854775807",
+            ),
+        ] {
+            assert_eq!(
+                Some(expected),
+                format_frame_sequence(&frames).as_deref(),
+                "{frames:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_format_inexpressible() {
+        for frames in [
+            vec![],
+            vec![1, 2, 3, 1],
+            vec![1, 2, 3, 2, 1, 2],
+            vec![5, 1, 6, 5],
+        ] {
+            assert_eq!(None, format_frame_sequence(&frames), "{frames:?}");
+        }
+        let style = FormatStyle {
+            allow_ping_pong: false,
+            ..Default::default()
+        };
+        assert_eq!(None, format_frame_sequence_with(&[1, 2, 1], &style));
+    }
+
+    #[test]
+    fn test_format_styles() {
+        let frames = [1, 3, 5, 7, 10, 9, 8, -2, -1, 0, 4, 4];
+        for (style, expected) in [
+            (FormatStyle::default(), "1-7@2,10-8,-2..0,4-4*2"),
+            (
+                FormatStyle {
+                    step_token: "x".into(),
+                    part_separator: " ".into(),
+                    range_separator: ":".into(),
+                    negative_range_separator: String::from(":").into(),
+                    ..Default::default()
+                },
+                "1:7x2 10:8 -2:0 4:4*2",
+            ),
+            (
+                FormatStyle {
+                    allow_steps: false,
+                    ..Default::default()
+                },
+                "1,3,5,7,10-8,-2..0,4-4*2",
+            ),
+            (
+                FormatStyle {
+                    allow_reverse: false,
+                    ..Default::default()
+                },
+                "1-7@2,10,9,8,-2..0,4-4*2",
+            ),
+            (
+                FormatStyle {
+                    max_run_length: 2,
+                    ..Default::default()
+                },
+                "1,3,5,7,10-9,8,-2,-1,0,4-4*2",
+            ),
+            (
+                FormatStyle {
+                    max_run_length: 1,
+                    ..Default::default()
+                },
+                "1,3,5,7,10,9,8,-2,-1,0,4-4*2",
+            ),
+            (
+                FormatStyle {
+                    allow_holds: false,
+                    ..Default::default()
+                },
+                "1-7@2,10-8,-2..0,4,4",
+            ),
+            (
+                FormatStyle {
+                    allow_holds: false,
+                    max_run_length: 1,
+                    ..Default::default()
+                },
+                "1,3,5,7,10,9,8,-2,-1,0,4,4",
+            ),
+        ] {
+            assert_eq!(
+                Some(expected),
+                format_frame_sequence_with(&frames, &style).as_deref()
+            );
+        }
+    }
+
+    #[test]
+    fn test_format_round_trip() {
+        let mut rng = SplitMix64::new(42);
+        for _ in 0..2000 {
+            let style = FormatStyle {
+                step_token: ["@", "x"][(rng.next_u64() % 2) as usize].into(),
+                negative_range_separator: ["..", ":"][(rng.next_u64() % 2) as usize].into(),
+                allow_steps: [true, false][(rng.next_u64() % 2) as usize],
+                allow_reverse: [true, false][(rng.next_u64() % 2) as usize],
+                allow_holds: [true, false][(rng.next_u64() % 2) as usize],
+                allow_ping_pong: [true, false][(rng.next_u64() % 2) as usize],
+                max_run_length: [1, 2, 3, usize::MAX][(rng.next_u64() % 4) as usize],
+                ..Default::default()
+            };
+            let length = rng.next_u64() % 20 + 1;
+            let spread = [3, 10, 1000, 0][(rng.next_u64() % 4) as usize];
+            let mut frames = Vec::<isize>::new();
+            for _ in 0..length {
+                let frame = match rng.next_u64() % 8 {
+                    // Continue a run.
+                    0..=3 if 2 <= frames.len() => {
+                        let [previous, last] = frames[frames.len() - 2..] else {
+                            unreachable!()
+                        };
+                        last.checked_mul(2)
+                            .and_then(|frame| frame.checked_sub(previous))
+                            .unwrap_or(last)
+                    }
+                    // Hold.
+                    4 if !frames.is_empty() => frames[frames.len() - 1],
+                    // Any frame.
+                    _ if 0 == spread => rng.next_u64() as isize,
+                    _ => (rng.next_u64() % spread) as isize - spread as isize / 2,
+                };
+                if !frames.contains(&frame) || frames.last() == Some(&frame) {
+                    frames.push(frame);
+                }
+            }
+            // Go back from the end of the last run to its start.
+            let held = frames
+                .iter()
+                .copied()
+                .dedup_with_count()
+                .collect::<Vec<_>>();
+            let mut ping_pong = false;
+            if 2 <= held.len() && 0 == rng.next_u64() % 4 {
+                let (hold, last) = held[held.len() - 1];
+                let step = last
+                    .checked_sub(held[held.len() - 2].1)
+                    .filter(|step| step.checked_abs().is_some());
+                let first = (0..held.len() - 1)
+                    .rev()
+                    .take_while(|index| {
+                        let (frame_hold, frame) = held[*index];
+                        hold == frame_hold
+                            && step.is_some()
+                            && held[index + 1].1.checked_sub(frame) == step
+                    })
+                    .last()
+                    .unwrap_or(held.len() - 1);
+                let back = held[first..held.len() - 1].iter().rev();
+                ping_pong = first < held.len() - 1;
+                frames.extend(back.flat_map(|(_, frame)| repeat_n(*frame, hold)));
+            }
+
+            let sequence = format_frame_sequence(&frames).unwrap();
+            assert_eq!(frames, parse(&sequence), "{sequence}");
+            let Some(sequence) = format_frame_sequence_with(&frames, &style) else {
+                // The way back can only be written as a ping-pong, which the
+                // style may not allow.
+                assert!(ping_pong, "{frames:?} {style:?}");
+                continue;
+            };
+            // Without holds, the parser removes the repetitions.
+            let expected = if style.allow_holds {
+                frames.clone()
+            } else {
+                let mut frames = frames.clone();
+                frames.dedup();
+                frames
+            };
+            assert_eq!(expected, parse(&sequence), "{sequence} {style:?}");
+        }
+    }
+
+    #[test]
+    fn test_format_round_trip_limits() {
+        // One part per frame, more than the default limits allow.
+        let frames = (0..=10_000).map(|frame| frame * frame).collect::<Vec<_>>();
+        let sequence = format_frame_sequence(&frames).unwrap();
+        assert!(matches!(
+            parse_frame_sequence(&sequence),
+            Err(FrameSequenceError::LimitExceeded(_))
+        ));
+        let options = ParseOptions {
+            limits: Limits::NONE,
+            ..Default::default()
+        };
+        assert_eq!(
+            frames,
+            parse_frame_sequence_with_options(&sequence, &options).unwrap()
+        );
+    }
+}
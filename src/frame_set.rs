@@ -0,0 +1,942 @@
+use crate::{
+    check_nesting,
+    dialect::{is_range_rule, is_sequence_rule},
+    frame_to_number, include_last_frame, is_range_modifier,
+    limits::{check_frame, check_input_length, check_part_count, run_count_error},
+    parse_as_is, parse_normalized, range_bound, shift, shift_frame, step_to_number,
+    FrameSequenceError, FrameSequenceParser, ParseOptions, Rule,
+};
+use itertools::Itertools;
+use pest::{
+    iterators::{Pair, Pairs},
+    Parser, Span,
+};
+use std::{ops::RangeInclusive, str::FromStr};
+
+/// An arithmetic progression of frames: a start frame, a step and a number
+/// of frames, e.g. `1-9@2` or `10-1`.
+///
+/// ```
+/// # use frame_sequence::FrameRun;
+/// let run = FrameRun::new(10, -3, 4).unwrap();
+/// assert_eq!(1, run.last());
+/// assert!(run.contains(4));
+/// assert_eq!([10, 7, 4, 1], run.iter().collect::<Vec<_>>().as_slice());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct FrameRun {
+    start: isize,
+    step: isize,
+    len: usize,
+}
+
+impl FrameRun {
+    /// A run of `len` frames from `start`, `step` apart. A step of zero
+    /// repeats `start`.
+    ///
+    /// Returns [`None`] if `len` is zero or the last frame does not fit an
+    /// [`isize`].
+    pub fn new(start: isize, step: isize, len: usize) -> Option<Self> {
+        let last = start as i128 + (len as i128 - 1) * step as i128;
+        (0 < len && isize::try_from(last).is_ok()).then(|| Self::new_unchecked(start, step, len))
+    }
+
+    /// A single frame.
+    pub(crate) fn single(frame: isize) -> Self {
+        Self::new_unchecked(frame, 1, 1)
+    }
+
+    fn new_unchecked(start: isize, step: isize, len: usize) -> Self {
+        // The step of a single frame is meaningless.
+        let step = if 1 == len { 1 } else { step };
+        Self { start, step, len }
+    }
+
+    /// The first frame.
+    pub fn start(&self) -> isize {
+        self.start
+    }
+
+    /// The distance from one frame to the next. Negative if the run is
+    /// backwards.
+    pub fn step(&self) -> isize {
+        self.step
+    }
+
+    /// The number of frames. Never zero.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// The last frame.
+    pub fn last(&self) -> isize {
+        self.nth(self.len - 1)
+    }
+
+    /// Returns `true` if `frame` is in the run.
+    pub fn contains(&self, frame: isize) -> bool {
+        self.index_of(frame).is_some()
+    }
+
+    /// The frames of the run, in order.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = isize> + ExactSizeIterator {
+        let run = *self;
+        (0..self.len).map(move |index| run.nth(index))
+    }
+
+    pub(crate) fn nth(&self, index: usize) -> isize {
+        (self.start as i128 + index as i128 * self.step as i128) as isize
+    }
+
+    fn index_of(&self, frame: isize) -> Option<usize> {
+        let offset = frame as i128 - self.start as i128;
+        if 0 == offset {
+            return Some(0);
+        }
+        if 1 == self.len || 0 == self.step || 0 != offset % self.step as i128 {
+            return None;
+        }
+        let index = offset / self.step as i128;
+        (0 < index && index < self.len as i128).then_some(index as usize)
+    }
+
+    /// The same frames, in ascending order and without repetitions.
+    fn ascending(self) -> Self {
+        match self.step {
+            0 => Self::single(self.start),
+            step if step < 0 => Self::new_unchecked(self.last(), -step, self.len),
+            _ => self,
+        }
+    }
+
+    /// Pushes the frames of `self` that are not in `other` to `runs`, in
+    /// order.
+    ///
+    /// Returns `false`, without pushing anything, if `runs` would then have
+    /// more than `max_runs` runs.
+    fn difference_into(&self, other: &FrameRun, runs: &mut Vec<FrameRun>, max_runs: usize) -> bool {
+        let (first, stride, count) = self.common_indices(other).unwrap_or((self.len, 1, 0));
+
+        // Before the first and after the last common frame, and between two
+        // of them, `stride - 1` frames. If that is a single frame, these
+        // form one run.
+        let end = (first + count.saturating_sub(1) * stride + 1).min(self.len);
+        let gaps = match stride {
+            1 => 0,
+            2 => usize::from(1 < count),
+            _ => count - 1,
+        };
+        let pieces = usize::from(0 < first) + gaps + usize::from(end < self.len);
+        if max_runs.saturating_sub(runs.len()) < pieces {
+            return false;
+        }
+
+        let mut push = |from: usize, step: isize, len: usize| {
+            if 0 < len {
+                runs.push(Self::new_unchecked(self.nth(from), step, len));
+            }
+        };
+        push(0, self.step, first);
+        match stride {
+            1 => (),
+            // The step only fits if there is more than one frame.
+            2 if 2 < count => push(first + 1, self.step * 2, count - 1),
+            2 => push(first + 1, self.step, count - 1),
+            _ => {
+                for index in 0..count - 1 {
+                    push(first + index * stride + 1, self.step, stride - 1);
+                }
+            }
+        }
+        push(end, self.step, self.len - end);
+        true
+    }
+
+    /// The indices of the frames of `self` that are also in `other`: the
+    /// first index, the distance between the indices and their number.
+    fn common_indices(&self, other: &FrameRun) -> Option<(usize, usize, usize)> {
+        if 1 == self.len || 0 == self.step {
+            return other.contains(self.start).then_some((0, 1, self.len));
+        }
+        if 1 == other.len || 0 == other.step {
+            return self.index_of(other.start).map(|index| (index, 1, 1));
+        }
+
+        let start = self.start as i128;
+        let step = self.step as i128;
+        let low = other.start.min(other.last()) as i128;
+        let high = other.start.max(other.last()) as i128;
+        let other_step = other.step.unsigned_abs() as i128;
+
+        // The indices of the frames within `low..=high`.
+        let (first, last) = if 0 < step {
+            (ceil_div(low - start, step), (high - start).div_euclid(step))
+        } else {
+            (
+                ceil_div(start - high, -step),
+                (start - low).div_euclid(-step),
+            )
+        };
+        let (first, last) = (first.max(0), last.min(self.len as i128 - 1));
+        if last < first {
+            return None;
+        }
+
+        // Of these, the ones with `start + index * step ≡ low` modulo the
+        // step of `other`.
+        let divisor = gcd(step.abs(), other_step);
+        let offset = low - start;
+        if 0 != offset % divisor {
+            return None;
+        }
+        let stride = other_step / divisor;
+        let residue = ((offset / divisor).rem_euclid(stride) as u128
+            * mod_inverse((step / divisor).rem_euclid(stride), stride) as u128
+            % stride as u128) as i128;
+        let first = first + (residue - first).rem_euclid(stride);
+
+        (first <= last).then(|| {
+            (
+                first as usize,
+                stride as usize,
+                ((last - first) / stride + 1) as usize,
+            )
+        })
+    }
+}
+
+fn ceil_div(dividend: i128, divisor: i128) -> i128 {
+    -(-dividend).div_euclid(divisor)
+}
+
+fn gcd(mut a: i128, mut b: i128) -> i128 {
+    while 0 != b {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// The inverse of `value` modulo `modulus`. They must be coprime.
+fn mod_inverse(value: i128, modulus: i128) -> i128 {
+    let (mut remainder, mut next_remainder) = (value, modulus);
+    let (mut inverse, mut next_inverse) = (1i128, 0i128);
+    while 0 != next_remainder {
+        let quotient = remainder / next_remainder;
+        (remainder, next_remainder) = (next_remainder, remainder - quotient * next_remainder);
+        (inverse, next_inverse) = (next_inverse, inverse - quotient * next_inverse);
+    }
+    inverse.rem_euclid(modulus)
+}
+
+/// The frames of `runs` that are not in `other`, in order, or [`None`] if
+/// these are more than `max_runs` runs.
+pub(crate) fn subtract(
+    runs: &[FrameRun],
+    other: &FrameRun,
+    max_runs: usize,
+) -> Option<Vec<FrameRun>> {
+    let mut result = Vec::with_capacity(runs.len());
+    for run in runs {
+        if !run.difference_into(other, &mut result, max_runs) {
+            return None;
+        }
+    }
+    Some(result)
+}
+
+/// The runs from `start` to `end` with a step of `step`.
+pub(crate) fn progression(start: isize, end: isize, step: usize) -> Vec<FrameRun> {
+    let step = if start <= end {
+        step as isize
+    } else {
+        -(step as isize)
+    };
+    match (start.abs_diff(end) / step.unsigned_abs()).checked_add(1) {
+        Some(len) => vec![FrameRun::new_unchecked(start, step, len)],
+        // All frames, more than a `usize` can count.
+        None => {
+            let half = 1 << (usize::BITS - 1);
+            let first = FrameRun::new_unchecked(start, step, half);
+            vec![
+                first,
+                FrameRun::new_unchecked(first.last() + step, step, half),
+            ]
+        }
+    }
+}
+
+/// Greedily joins consecutive frames with the same distance into runs.
+fn compress(frames: impl IntoIterator<Item = isize>) -> Vec<FrameRun> {
+    join_runs(frames.into_iter().map(FrameRun::single))
+}
+
+/// Joins runs the way [`compress()`] joins their frames, without expanding
+/// them. The same frames so always end up in the same runs.
+fn join_runs(runs: impl IntoIterator<Item = FrameRun>) -> Vec<FrameRun> {
+    let mut joined = Vec::<FrameRun>::new();
+    for run in runs {
+        let mut rest = Some(run);
+        if let Some(last) = joined.last_mut() {
+            // The step `last` continues with, if `run` starts there.
+            let step = if 1 == last.len {
+                run.start.checked_sub(last.start)
+            } else {
+                (last.last().checked_add(last.step) == Some(run.start)).then_some(last.step)
+            };
+            if let Some(step) = step {
+                // The rest of `run` only continues it with the same step.
+                let taken = if run.step == step { run.len } else { 1 };
+                if let Some(len) = last.len.checked_add(taken) {
+                    *last = FrameRun::new_unchecked(last.start, step, len);
+                    rest = (taken < run.len)
+                        .then(|| FrameRun::new_unchecked(run.nth(1), run.step, run.len - 1));
+                }
+            }
+        }
+        joined.extend(rest);
+    }
+    joined
+}
+
+/// Returns `true` if the frames of ascending runs interleave.
+fn interleave(runs: &[FrameRun]) -> bool {
+    runs.iter()
+        .tuple_windows()
+        .any(|(run, next)| next.start <= run.last())
+}
+
+pub(crate) fn count(runs: &[FrameRun]) -> usize {
+    runs.iter()
+        .fold(0usize, |count, run| count.saturating_add(run.len))
+}
+
+/// An ordered list of frames, stored as [`FrameRun`]s.
+///
+/// Unlike a [`Vec`]`<`[`isize`]`>`, the size of a `FrameList` depends on
+/// the number of runs, not frames. The same frames are always stored in the
+/// same runs, so comparing lists compares their runs:
+///
+/// ```
+/// # use frame_sequence::FrameList;
+/// let list = "1-1000000000000,!500".parse::<FrameList>().unwrap();
+/// assert_eq!(999_999_999_999, list.len());
+/// assert_eq!(2, list.runs().len());
+/// assert!(!list.contains(500));
+/// assert_eq!(Some(1_000_000_000_000), list.last());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FrameList {
+    runs: Vec<FrameRun>,
+}
+
+impl FrameList {
+    /// An empty list.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The runs of the list, in order.
+    pub fn runs(&self) -> &[FrameRun] {
+        &self.runs
+    }
+
+    /// The number of frames, saturating at [`usize::MAX`].
+    pub fn len(&self) -> usize {
+        count(&self.runs)
+    }
+
+    /// Returns `true` if the list has no frames.
+    pub fn is_empty(&self) -> bool {
+        self.runs.is_empty()
+    }
+
+    /// Returns `true` if `frame` is in the list.
+    pub fn contains(&self, frame: isize) -> bool {
+        self.runs.iter().any(|run| run.contains(frame))
+    }
+
+    /// The first frame of the list.
+    pub fn first(&self) -> Option<isize> {
+        self.runs.first().map(FrameRun::start)
+    }
+
+    /// The last frame of the list.
+    pub fn last(&self) -> Option<isize> {
+        self.runs.last().map(FrameRun::last)
+    }
+
+    /// The frames of the list, in order.
+    pub fn iter(&self) -> impl Iterator<Item = isize> + '_ {
+        self.runs.iter().flat_map(FrameRun::iter)
+    }
+}
+
+impl From<Vec<isize>> for FrameList {
+    fn from(frames: Vec<isize>) -> Self {
+        frames.into_iter().collect()
+    }
+}
+
+impl FromIterator<isize> for FrameList {
+    fn from_iter<T: IntoIterator<Item = isize>>(frames: T) -> Self {
+        Self {
+            runs: compress(frames),
+        }
+    }
+}
+
+impl From<FrameList> for Vec<isize> {
+    fn from(list: FrameList) -> Self {
+        list.iter().collect()
+    }
+}
+
+impl From<RangeInclusive<isize>> for FrameList {
+    fn from(range: RangeInclusive<isize>) -> Self {
+        Self {
+            runs: if range.is_empty() {
+                Vec::new()
+            } else {
+                join_runs(progression(*range.start(), *range.end(), 1))
+            },
+        }
+    }
+}
+
+impl TryFrom<FrameList> for RangeInclusive<isize> {
+    /// The list, if it is not a single ascending range.
+    type Error = FrameList;
+
+    fn try_from(list: FrameList) -> Result<Self, Self::Error> {
+        let is_range = list.runs.iter().all(|run| 1 == run.step)
+            && list
+                .runs
+                .iter()
+                .tuple_windows()
+                .all(|(run, next)| run.last().checked_add(1) == Some(next.start));
+        match (is_range, list.first(), list.last()) {
+            (true, Some(first), Some(last)) => Ok(first..=last),
+            _ => Err(list),
+        }
+    }
+}
+
+impl FromStr for FrameList {
+    type Err = FrameSequenceError;
+
+    /// Parses with the default [`ParseOptions`].
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        parse_frame_list(input, &ParseOptions::default())
+    }
+}
+
+/// A set of frames, stored as ascending [`FrameRun`]s without common frames.
+///
+/// Iterating a `FrameSet` yields its frames in ascending order. Comparing
+/// sets only iterates them if the frames of their runs interleave, e.g. for
+/// `1-9@2,2-10@2`.
+///
+/// ```
+/// # use frame_sequence::FrameSet;
+/// let set = "1-1000000@2,10-1".parse::<FrameSet>().unwrap();
+/// assert_eq!(500_005, set.len());
+/// assert!(set.contains(2));
+/// assert!(!set.contains(12));
+/// assert_eq!(
+///     [1, 2, 3, 4, 5],
+///     set.iter().take(5).collect::<Vec<_>>().as_slice()
+/// );
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct FrameSet {
+    /// Sorted by their start.
+    runs: Vec<FrameRun>,
+}
+
+impl FrameSet {
+    /// An empty set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The runs of the set, sorted by their start. The frames of different
+    /// runs may interleave.
+    pub fn runs(&self) -> &[FrameRun] {
+        &self.runs
+    }
+
+    /// The number of frames, saturating at [`usize::MAX`].
+    pub fn len(&self) -> usize {
+        count(&self.runs)
+    }
+
+    /// Returns `true` if the set has no frames.
+    pub fn is_empty(&self) -> bool {
+        self.runs.is_empty()
+    }
+
+    /// Returns `true` if `frame` is in the set.
+    pub fn contains(&self, frame: isize) -> bool {
+        self.runs.iter().any(|run| run.contains(frame))
+    }
+
+    /// The smallest frame of the set.
+    pub fn first(&self) -> Option<isize> {
+        self.runs.first().map(FrameRun::start)
+    }
+
+    /// The largest frame of the set.
+    pub fn last(&self) -> Option<isize> {
+        self.runs.iter().map(FrameRun::last).max()
+    }
+
+    /// The frames of the set, in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = isize> + '_ {
+        self.runs.iter().map(FrameRun::iter).kmerge()
+    }
+}
+
+impl PartialEq for FrameSet {
+    fn eq(&self, other: &Self) -> bool {
+        // Runs that do not interleave are joined like the runs of a list.
+        self.runs == other.runs
+            || (interleave(&self.runs) || interleave(&other.runs))
+                && self.len() == other.len()
+                && self.iter().eq(other.iter())
+    }
+}
+
+impl Eq for FrameSet {}
+
+impl From<FrameList> for FrameSet {
+    /// Removes repeated frames, without expanding the runs of the list.
+    fn from(list: FrameList) -> Self {
+        // The runs of a parsed list have no common frames and are kept as
+        // they are. Otherwise there are at most as many runs as frames.
+        let mut runs = Vec::<FrameRun>::new();
+        for run in list.runs {
+            let pieces = runs.iter().fold(vec![run.ascending()], |pieces, seen| {
+                subtract(&pieces, seen, usize::MAX).unwrap()
+            });
+            runs.extend(pieces);
+        }
+        runs.sort_unstable_by_key(|run| run.start);
+        if !interleave(&runs) {
+            runs = join_runs(runs);
+        }
+        Self { runs }
+    }
+}
+
+impl From<Vec<isize>> for FrameSet {
+    fn from(mut frames: Vec<isize>) -> Self {
+        frames.sort_unstable();
+        frames.dedup();
+        Self {
+            runs: compress(frames),
+        }
+    }
+}
+
+impl FromIterator<isize> for FrameSet {
+    fn from_iter<T: IntoIterator<Item = isize>>(frames: T) -> Self {
+        frames.into_iter().collect::<Vec<_>>().into()
+    }
+}
+
+impl From<FrameSet> for Vec<isize> {
+    fn from(set: FrameSet) -> Self {
+        set.iter().collect()
+    }
+}
+
+impl From<RangeInclusive<isize>> for FrameSet {
+    fn from(range: RangeInclusive<isize>) -> Self {
+        Self {
+            runs: FrameList::from(range).runs,
+        }
+    }
+}
+
+impl TryFrom<FrameSet> for RangeInclusive<isize> {
+    /// The set, if it has gaps.
+    type Error = FrameSet;
+
+    fn try_from(set: FrameSet) -> Result<Self, Self::Error> {
+        let count = set.runs.iter().map(|run| run.len as u128).sum::<u128>();
+        match (set.first(), set.last()) {
+            // The frames are unique, so there is no gap if there are as many
+            // as the range has.
+            (Some(first), Some(last)) if last.abs_diff(first) as u128 + 1 == count => {
+                Ok(first..=last)
+            }
+            _ => Err(set),
+        }
+    }
+}
+
+impl FromStr for FrameSet {
+    type Err = FrameSequenceError;
+
+    /// Parses with the default [`ParseOptions`].
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        parse_frame_list(input, &ParseOptions::default()).map(Self::from)
+    }
+}
+
+/// Parse a frame sequence string into a [`FrameList`].
+///
+/// The result is the same as with
+/// [`parse_frame_sequence_with_options()`](crate::parse_frame_sequence_with_options).
+/// But as long as a sequence only consists of frames and ranges with an
+/// optional step size, `e` and shift, and of exclusions of these, it is
+/// parsed in runs, without expanding it. The number of frames is then only
+/// limited by the number of runs, which must not exceed
+/// [`Limits::max_frames`](crate::Limits::max_frames).
+///
+/// ```
+/// # use frame_sequence::{parse_frame_list, FrameSet, ParseOptions};
+/// let list = parse_frame_list("1-2000000000@2,!1-1000@3", &ParseOptions::default()).unwrap();
+/// assert_eq!(Some(3), list.first());
+///
+/// let set = FrameSet::from(list);
+/// assert_eq!(999_999_833, set.len());
+/// ```
+pub fn parse_frame_list(
+    input: &str,
+    options: &ParseOptions,
+) -> Result<FrameList, FrameSequenceError> {
+    parse_normalized(input, options, parse_runs)
+}
+
+fn parse_runs(input: &str, options: &ParseOptions) -> Result<FrameList, FrameSequenceError> {
+    check_input_length(input, &options.limits)?;
+    check_nesting(input)?;
+    let token_tree = FrameSequenceParser::parse(options.dialect.rule(), input)?;
+    if !is_run_sequence(token_tree.clone(), false) {
+        return parse_as_is(input, options).map(FrameList::from);
+    }
+    check_part_count(input, token_tree.clone(), options)?;
+
+    let mut parts = Vec::new();
+    let mut exclusions = Vec::new();
+    collect_parts(token_tree, &mut parts, &mut exclusions);
+    let to_runs = |pairs: Vec<Pair<Rule>>| {
+        pairs
+            .into_iter()
+            .map(|pair| part_to_runs(pair, options))
+            .flatten_ok()
+            .collect::<Result<Vec<_>, _>>()
+    };
+    let (parts, exclusions) = (to_runs(parts)?, to_runs(exclusions)?);
+
+    // Remove duplicates and excluded frames. The number of runs is checked
+    // before they are made, as the frames of one run can be scattered over
+    // as many runs.
+    let span = Span::new(input, 0, input.len()).unwrap();
+    let max_runs = options.limits.max_frames;
+    let mut runs = Vec::<FrameRun>::new();
+    for part in parts {
+        let pieces = runs
+            .iter()
+            .try_fold(vec![part], |pieces, seen| {
+                subtract(&pieces, seen, max_runs.saturating_sub(runs.len()))
+            })
+            .ok_or_else(|| run_count_error(span, options))?;
+        runs.extend(pieces);
+        if max_runs < runs.len() {
+            return Err(run_count_error(span, options));
+        }
+    }
+    for exclusion in &exclusions {
+        runs =
+            subtract(&runs, exclusion, max_runs).ok_or_else(|| run_count_error(span, options))?;
+    }
+
+    Ok(FrameList {
+        runs: join_runs(runs),
+    })
+}
+
+/// Whether a sequence only consists of frames and ranges with an optional
+/// step size, `e` and shift, and of exclusions of these. With `binary`, the
+/// ranges may also be split.
+pub(crate) fn is_run_sequence(pairs: Pairs<Rule>, binary: bool) -> bool {
+    !pairs.flatten().any(|pair| match pair.as_rule() {
+        Rule::BinarySequenceSymbol => !binary,
+        Rule::AmbiguousFrameRange
+        | Rule::AmbiguousShift
+        | Rule::AmbiguousSymbolRange
+        | Rule::AmbiguousGroup
+        | Rule::Group
+        | Rule::RandomSymbol
+        | Rule::PassList
+        | Rule::FrameCount
+        | Rule::PingPongSymbol
+        | Rule::Hold => true,
+        _ => false,
+    })
+}
+
+/// Collects the frames and ranges of a [run sequence](is_run_sequence) and
+/// of its exclusions.
+pub(crate) fn collect_parts<'a>(
+    pairs: Pairs<'a, Rule>,
+    parts: &mut Vec<Pair<'a, Rule>>,
+    exclusions: &mut Vec<Pair<'a, Rule>>,
+) {
+    for pair in pairs {
+        match pair.as_rule() {
+            Rule::Exclusion => collect_parts(pair.into_inner(), exclusions, &mut Vec::new()),
+            rule if is_sequence_rule(rule) => collect_parts(pair.into_inner(), parts, exclusions),
+            rule if is_range_rule(rule) || Rule::Frame == rule || Rule::Expression == rule => {
+                parts.push(pair)
+            }
+            _ => (),
+        }
+    }
+}
+
+/// The runs of a frame or a range without binary splitting.
+pub(crate) fn part_to_runs(
+    pair: Pair<Rule>,
+    options: &ParseOptions,
+) -> Result<Vec<FrameRun>, FrameSequenceError> {
+    if is_range_rule(pair.as_rule()) {
+        return frame_range_to_runs(pair, options);
+    }
+    let span = pair.as_span();
+    Ok(vec![FrameRun::single(check_frame(
+        frame_to_number(pair, options)?,
+        span,
+        options,
+    )?)])
+}
+
+fn frame_range_to_runs(
+    pair: Pair<Rule>,
+    options: &ParseOptions,
+) -> Result<Vec<FrameRun>, FrameSequenceError> {
+    let span = pair.as_span();
+    let mut pairs = pair.clone().into_inner();
+    let left = check_frame(
+        range_bound(pairs.next().unwrap(), span, options)?,
+        span,
+        options,
+    )?;
+    let right = check_frame(
+        range_bound(pairs.next().unwrap(), span, options)?,
+        span,
+        options,
+    )?;
+    let include_last = include_last_frame(&pairs, options);
+    let step = match pairs.find(is_range_modifier) {
+        Some(step) => step_to_number(step, options)?,
+        None => 1,
+    };
+
+    let mut runs = progression(left, right, step);
+    if include_last && runs.last().map(FrameRun::last) != Some(right) {
+        runs.push(FrameRun::single(right));
+    }
+
+    let shift = shift(&pair, options)?;
+    runs.into_iter()
+        .map(|run| {
+            // The frames of a run are monotonic, so checking its ends checks
+            // all of them.
+            let start = check_frame(shift_frame(run.start, shift)?, shift.1, options)?;
+            check_frame(shift_frame(run.last(), shift)?, shift.1, options)?;
+            Ok(FrameRun { start, ..run })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compress, join_runs};
+    use crate::{
+        parse_frame_list, parse_frame_sequence_with_options, Dialect, FrameContext, FrameList,
+        FrameRun, FrameSequenceError, FrameSet, Limits, ParseOptions,
+    };
+    use std::{collections::HashSet, ops::RangeInclusive};
+
+    #[test]
+    fn test_frame_run_difference() {
+        let runs = [
+            (0, 1, 20),
+            (19, -1, 20),
+            (3, 2, 8),
+            (17, -3, 6),
+            (-5, 4, 7),
+            (6, 0, 3),
+            (8, 1, 1),
+            (2, 5, 4),
+            (20, -7, 5),
+        ]
+        .map(|(start, step, len)| FrameRun::new(start, step, len).unwrap());
+
+        for run in &runs {
+            for other in &runs {
+                let mut result = Vec::new();
+                assert!(run.difference_into(other, &mut result, usize::MAX));
+                let excluded = other.iter().collect::<HashSet<_>>();
+                assert_eq!(
+                    run.iter()
+                        .filter(|frame| !excluded.contains(frame))
+                        .collect::<Vec<_>>(),
+                    result.iter().flat_map(FrameRun::iter).collect::<Vec<_>>(),
+                    "{run:?} - {other:?}"
+                );
+                assert_eq!(
+                    compress(run.iter().chain(other.iter())),
+                    join_runs([*run, *other]),
+                    "{run:?}, {other:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_frame_list() {
+        let options = ParseOptions {
+            context: FrameContext {
+                first: Some(1001),
+                last: Some(1100),
+                current: None,
+            },
+            ..Default::default()
+        };
+        for input in [
+            "1-10",
+            "10-1@3",
+            "1-20@3,2-30@4,!10-15",
+            "1-10^3-5,8",
+            "80-70@4e",
+            "1-5+10,12",
+            "1050-,..1005,first+2",
+            "1-3*2,2-4",
+            "(1-5,10-15)@3,!4",
+            "1-10@b,5-12",
+        ] {
+            assert_eq!(
+                parse_frame_sequence_with_options(input, &options).unwrap(),
+                Vec::from(parse_frame_list(input, &options).unwrap()),
+                "{input}"
+            );
+        }
+
+        let options = ParseOptions {
+            dialect: Dialect::Nuke,
+            ..Default::default()
+        };
+        assert_eq!(
+            [1, 6, 11, 20],
+            Vec::from(parse_frame_list("1-15x5 20", &options).unwrap()).as_slice()
+        );
+    }
+
+    #[test]
+    fn test_parse_huge_frame_list() {
+        let list = "-9223372036854775807..9223372036854775807"
+            .parse::<FrameList>()
+            .unwrap();
+        assert_eq!(usize::MAX, list.len());
+        assert_eq!(Some(isize::MAX), list.last());
+
+        let options = ParseOptions {
+            limits: Limits {
+                max_frames: 10,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert!(parse_frame_list("1-1000000000000", &options).is_ok());
+        assert!(parse_frame_list("1-1000000000000,!2-100@10", &options).is_err());
+        assert!(parse_frame_list("1-100@b", &options).is_err());
+        assert!("1-9223372036854775807+1".parse::<FrameList>().is_err());
+
+        // Removing every other frame leaves a run of the others.
+        let list = parse_frame_list(
+            "1-1000000000000,!1-1000000000000@2",
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(500_000_000_000, list.len());
+        assert_eq!(1, list.runs().len());
+        let set = "1-1000000000000@2,1-1000000000000@3"
+            .parse::<FrameSet>()
+            .unwrap();
+        assert_eq!(666_666_666_667, set.len());
+        // Removing every third frame leaves a run for every two.
+        let error = parse_frame_list(
+            "1-1000000000000,!1-1000000000000@3",
+            &ParseOptions::default(),
+        )
+        .unwrap_err();
+        assert!(matches!(error, FrameSequenceError::LimitExceeded(_)));
+        assert!(error.message().contains("10000000 runs"), "{error}");
+    }
+
+    #[test]
+    fn test_frame_list() {
+        let list = FrameList::from(vec![1, 2, 3, 5, 7, 9, 20, 20]);
+        assert_eq!(8, list.len());
+        assert_eq!(3, list.runs().len());
+        assert_eq!((Some(1), Some(20)), (list.first(), list.last()));
+        assert!(list.contains(7) && !list.contains(8));
+        assert_eq!(list, [1, 2, 3, 5, 7, 9, 20, 20].into_iter().collect());
+
+        let list = "1-1000000000000".parse::<FrameList>().unwrap();
+        let other = "1-500000000000,500000000001-1000000000000"
+            .parse::<FrameList>()
+            .unwrap();
+        assert_eq!(list, other);
+        assert_eq!(1, other.runs().len());
+        assert_eq!(
+            FrameList::from(vec![1, 3, 4, 5]),
+            "1-3@2,4-5".parse::<FrameList>().unwrap()
+        );
+        assert_ne!(list, "1-1000000000000,!5".parse::<FrameList>().unwrap());
+
+        assert_eq!(FrameList::from(vec![4, 5, 6]), FrameList::from(4..=6));
+        assert_eq!(
+            Ok(4..=6),
+            RangeInclusive::try_from(FrameList::from(vec![4, 5, 6]))
+        );
+        assert!(RangeInclusive::try_from(FrameList::from(vec![6, 5, 4])).is_err());
+        assert!(FrameList::from(RangeInclusive::new(1, 0)).is_empty());
+    }
+
+    #[test]
+    fn test_frame_set() {
+        let set = FrameSet::from(vec![9, 3, 1, 2, 3, 7, 5]);
+        assert_eq!([1, 2, 3, 5, 7, 9], Vec::from(set.clone()).as_slice());
+        assert_eq!((Some(1), Some(9)), (set.first(), set.last()));
+
+        let set = FrameSet::from("1-30@3,30-1@2,5".parse::<FrameList>().unwrap());
+        let expected = (1..=30)
+            .filter(|frame| 1 == frame % 3 || 0 == frame % 2 || 5 == *frame)
+            .collect::<Vec<_>>();
+        assert_eq!(expected.len(), set.len());
+        assert_eq!(expected, Vec::from(set.clone()));
+        assert_eq!(FrameSet::from(expected), set);
+
+        let set = "10-1,5-15".parse::<FrameSet>().unwrap();
+        assert_eq!(Ok(1..=15), RangeInclusive::try_from(set));
+        assert!(RangeInclusive::try_from(FrameSet::from(vec![1, 3])).is_err());
+        assert_eq!(FrameSet::from(1..=3), FrameSet::from(vec![3, 1, 2]));
+
+        let set = "1000000000000-1".parse::<FrameSet>().unwrap();
+        let other = "1-500000000000,1000000000000-500000000001"
+            .parse::<FrameSet>()
+            .unwrap();
+        assert_eq!(set, other);
+        assert_eq!(1, other.runs().len());
+        assert_eq!(
+            FrameSet::from(1..=10),
+            "1-9@2,2-10@2".parse::<FrameSet>().unwrap()
+        );
+    }
+}
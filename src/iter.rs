@@ -0,0 +1,467 @@
+use crate::{
+    check_nesting,
+    frame_set::{collect_parts, count, is_run_sequence, part_to_runs, progression, subtract},
+    limits::{
+        check_frame, check_frame_count, check_input_length, check_part_count, run_count_error,
+    },
+    parse_as_is, parse_normalized, range_bound, shift, shift_frame, FrameRun, FrameSequenceError,
+    FrameSequenceParser, ParseOptions, Rule,
+};
+use itertools::Itertools;
+use pest::{iterators::Pair, Parser, Span};
+use std::{iter::FusedIterator, vec};
+
+/// An iterator over the frames of a sequence, expanding it lazily.
+///
+/// Returned by [`frame_sequence_iter()`] and
+/// [`frame_sequence_iter_with_options()`].
+#[derive(Clone, Debug)]
+pub struct FrameSequenceIter {
+    parts: Vec<Part>,
+    /// The part the next frame is taken from.
+    index: usize,
+}
+
+/// Where the frames of a part of a sequence come from, without duplicates
+/// and excluded frames.
+#[derive(Clone, Debug)]
+enum Part {
+    /// The frames of the runs, in order. The position of the next frame.
+    Runs {
+        runs: Vec<FrameRun>,
+        run: usize,
+        frame: usize,
+    },
+    /// A `@b` range and its shift.
+    Binary {
+        frames: BinaryFrames,
+        shift: isize,
+        /// The runs of the frames to skip. Empty if there are none.
+        skipped: Vec<FrameRun>,
+        /// The number of frames left to yield.
+        remaining: usize,
+    },
+    /// A sequence that can not be expanded lazily.
+    Expanded(vec::IntoIter<isize>),
+}
+
+impl Part {
+    fn next_frame(&mut self) -> Option<isize> {
+        match self {
+            Part::Runs { runs, run, frame } => {
+                let current = runs.get(*run)?;
+                let next = current.nth(*frame);
+                *frame += 1;
+                if current.len() == *frame {
+                    *run += 1;
+                    *frame = 0;
+                }
+                Some(next)
+            }
+            Part::Binary {
+                frames,
+                shift,
+                skipped,
+                remaining,
+            } => {
+                while 0 < *remaining {
+                    // The shift was checked for all frames of the range.
+                    let frame = frames.next()? + *shift;
+                    if !skipped.iter().any(|run| run.contains(frame)) {
+                        *remaining -= 1;
+                        return Some(frame);
+                    }
+                }
+                None
+            }
+            Part::Expanded(frames) => frames.next(),
+        }
+    }
+}
+
+impl Iterator for FrameSequenceIter {
+    type Item = isize;
+
+    fn next(&mut self) -> Option<isize> {
+        loop {
+            match self.parts.get_mut(self.index)?.next_frame() {
+                Some(frame) => return Some(frame),
+                None => self.index += 1,
+            }
+        }
+    }
+}
+
+impl FusedIterator for FrameSequenceIter {}
+
+/// The frames of `low-high@b`, or of `high-low@b` if reversed, generated
+/// level by level while only storing the path to the current split.
+#[derive(Clone, Debug)]
+struct BinaryFrames {
+    low: isize,
+    high: isize,
+    reverse: bool,
+    /// The number of levels that split a range.
+    levels: u32,
+    /// How many steps, the levels and the ends, are done.
+    done: u32,
+    /// The ranges left to split on the current level, and their depth.
+    stack: Vec<(isize, isize, u32)>,
+}
+
+impl BinaryFrames {
+    fn new(start: isize, end: isize) -> Self {
+        let (low, high, reverse) = if start <= end {
+            (start, end, false)
+        } else {
+            (end, start, true)
+        };
+        // Each level halves the longest range until it can not be split.
+        let distance = low.abs_diff(high);
+        let levels = if distance < 2 {
+            0
+        } else {
+            (distance - 1).ilog2() + 1
+        };
+
+        Self {
+            low,
+            high,
+            reverse,
+            levels,
+            done: 0,
+            stack: Vec::new(),
+        }
+    }
+
+    /// What the next step yields. Coarse to fine, or the reverse of that.
+    fn step(&self) -> Option<BinaryStep> {
+        let ends = [self.low, self.high];
+        let ends = if self.low == self.high {
+            &ends[..1]
+        } else {
+            &ends[..]
+        };
+        let done = self.done as usize;
+        let levels = self.levels as usize;
+
+        if self.reverse {
+            if done < levels {
+                Some(BinaryStep::Level((levels - done) as u32))
+            } else {
+                ends.iter()
+                    .rev()
+                    .nth(done - levels)
+                    .copied()
+                    .map(BinaryStep::End)
+            }
+        } else if done < ends.len() {
+            Some(BinaryStep::End(ends[done]))
+        } else {
+            (done - ends.len() < levels).then(|| BinaryStep::Level((done - ends.len() + 1) as u32))
+        }
+    }
+}
+
+enum BinaryStep {
+    /// The middles of the ranges split on this level.
+    Level(u32),
+    /// The start or end of the range.
+    End(isize),
+}
+
+impl Iterator for BinaryFrames {
+    type Item = isize;
+
+    fn next(&mut self) -> Option<isize> {
+        loop {
+            let level = match self.step()? {
+                BinaryStep::Level(level) => level,
+                BinaryStep::End(end) => {
+                    self.done += 1;
+                    return Some(end);
+                }
+            };
+
+            if self.stack.is_empty() {
+                self.stack.push((self.low, self.high, 0));
+            }
+            while let Some((low, high, depth)) = self.stack.pop() {
+                if high.abs_diff(low) < 2 {
+                    continue;
+                }
+                // Rounds towards zero, like `binary_sequence()`.
+                let middle = ((low as i128 + high as i128) / 2) as isize;
+                if depth + 1 == level {
+                    if self.stack.is_empty() {
+                        self.done += 1;
+                    }
+                    return Some(middle);
+                }
+                // Visit the left half first, or the right one if reversed.
+                let halves = [(middle, high, depth + 1), (low, middle, depth + 1)];
+                if self.reverse {
+                    self.stack.extend(halves.into_iter().rev());
+                } else {
+                    self.stack.extend(halves);
+                }
+            }
+            self.done += 1;
+        }
+    }
+}
+
+/// Parse a frame sequence string into an iterator over its frames.
+///
+/// Frames and ranges with an optional step size, `e`, shift or binary
+/// splitting, and exclusions of these, are expanded one frame at a time.
+/// Duplicates and excluded frames are removed from their runs up front,
+/// binary splitting skips them as it goes. The memory used only depends on
+/// the number of parts and runs, not frames:
+///
+/// ```
+/// # use frame_sequence::frame_sequence_iter;
+/// let mut frames = frame_sequence_iter("1-1000000000000@b,!500000000000").unwrap();
+/// assert_eq!(Some(1), frames.next());
+/// assert_eq!(Some(1_000_000_000_000), frames.next());
+/// assert_eq!(Some(250_000_000_000), frames.next());
+/// ```
+///
+/// Other sequences, e.g. with groups or random order, are expanded up
+/// front.
+///
+/// All errors are reported here, the iterator itself can not fail. The
+/// frames are the same as with [`parse_frame_sequence()`](crate::parse_frame_sequence).
+pub fn frame_sequence_iter(input: &str) -> Result<FrameSequenceIter, FrameSequenceError> {
+    frame_sequence_iter_with_options(input, &ParseOptions::default())
+}
+
+/// Parse a frame sequence string into an iterator over its frames with the
+/// given [`ParseOptions`].
+///
+/// See [`frame_sequence_iter()`]. [`Limits::max_frames`](crate::Limits::max_frames)
+/// limits the number of runs left after removing duplicates and excluded
+/// frames, the number of frames binary splitting skips per part, and the
+/// frames of sequences that are expanded up front.
+pub fn frame_sequence_iter_with_options(
+    input: &str,
+    options: &ParseOptions,
+) -> Result<FrameSequenceIter, FrameSequenceError> {
+    parse_normalized(input, options, parse_iter)
+}
+
+fn parse_iter(
+    input: &str,
+    options: &ParseOptions,
+) -> Result<FrameSequenceIter, FrameSequenceError> {
+    check_input_length(input, &options.limits)?;
+    check_nesting(input)?;
+    let token_tree = FrameSequenceParser::parse(options.dialect.rule(), input)?;
+
+    if !is_run_sequence(token_tree.clone(), true) {
+        return Ok(FrameSequenceIter {
+            parts: vec![Part::Expanded(parse_as_is(input, options)?.into_iter())],
+            index: 0,
+        });
+    }
+    check_part_count(input, token_tree.clone(), options)?;
+
+    let mut pairs = Vec::new();
+    let mut exclusions = Vec::new();
+    collect_parts(token_tree, &mut pairs, &mut exclusions);
+    let exclusions = exclusions
+        .into_iter()
+        .map(|pair| pair_to_part(pair, options).map(|(_, runs)| runs))
+        .flatten_ok()
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Remove duplicates and excluded frames from the runs of each part up
+    // front, like `parse_frame_list()` does.
+    let span = Span::new(input, 0, input.len()).unwrap();
+    let max_runs = options.limits.max_frames;
+    let mut run_count = 0usize;
+    // The runs of the exclusions and of all earlier parts.
+    let mut skipped = exclusions;
+    let mut parts = Vec::new();
+    for pair in pairs {
+        let part_span = pair.as_span();
+        let (part, runs) = pair_to_part(pair, options)?;
+        let left = skipped
+            .iter()
+            .try_fold(runs.clone(), |pieces, seen| {
+                subtract(&pieces, seen, max_runs.saturating_sub(run_count))
+            })
+            .ok_or_else(|| run_count_error(span, options))?;
+        run_count += left.len();
+
+        parts.push(match part {
+            Part::Runs { run, frame, .. } => Part::Runs {
+                runs: left,
+                run,
+                frame,
+            },
+            Part::Binary { frames, shift, .. } => {
+                // Binary splitting stops once all frames that are left were
+                // yielded, so it skips nothing if there are none.
+                let remaining = count(&left);
+                let skipped = if 0 == remaining || count(&runs) == remaining {
+                    Vec::new()
+                } else {
+                    check_frame_count((count(&runs) - remaining) as u128, part_span, options)?;
+                    skipped.clone()
+                };
+                Part::Binary {
+                    frames,
+                    shift,
+                    skipped,
+                    remaining,
+                }
+            }
+            part => part,
+        });
+        skipped.extend(runs);
+    }
+
+    Ok(FrameSequenceIter { parts, index: 0 })
+}
+
+/// The part of a frame or range, with all its runs.
+fn pair_to_part(
+    pair: Pair<Rule>,
+    options: &ParseOptions,
+) -> Result<(Part, Vec<FrameRun>), FrameSequenceError> {
+    if !pair
+        .clone()
+        .into_inner()
+        .any(|pair| Rule::BinarySequenceSymbol == pair.as_rule())
+    {
+        return Ok((
+            Part::Runs {
+                runs: Vec::new(),
+                run: 0,
+                frame: 0,
+            },
+            part_to_runs(pair, options)?,
+        ));
+    }
+
+    let span = pair.as_span();
+    let mut pairs = pair.clone().into_inner();
+    let left = check_frame(
+        range_bound(pairs.next().unwrap(), span, options)?,
+        span,
+        options,
+    )?;
+    let right = check_frame(
+        range_bound(pairs.next().unwrap(), span, options)?,
+        span,
+        options,
+    )?;
+
+    // Binary splitting yields all frames of the range.
+    let shift = shift(&pair, options)?;
+    let low = check_frame(shift_frame(left.min(right), shift)?, shift.1, options)?;
+    let high = check_frame(shift_frame(left.max(right), shift)?, shift.1, options)?;
+
+    let runs = progression(low, high, 1);
+    Ok((
+        Part::Binary {
+            frames: BinaryFrames::new(left, right),
+            shift: shift.0,
+            skipped: Vec::new(),
+            remaining: count(&runs),
+        },
+        runs,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BinaryFrames;
+    use crate::{
+        binary_sequence, frame_sequence_iter, frame_sequence_iter_with_options,
+        parse_frame_sequence, Dialect, Limits, ParseOptions,
+    };
+    use itertools::Itertools;
+
+    #[test]
+    fn test_binary_frames() {
+        for start in -40..40 {
+            for end in -40..40 {
+                assert_eq!(
+                    binary_sequence((start, end))
+                        .into_iter()
+                        .unique()
+                        .collect::<Vec<_>>(),
+                    BinaryFrames::new(start, end).collect::<Vec<_>>(),
+                    "{start}-{end}@b"
+                );
+            }
+        }
+        let extreme = BinaryFrames::new(isize::MIN, isize::MAX);
+        assert_eq!(
+            [isize::MIN, isize::MAX, 0, -4611686018427387904],
+            extreme.take(4).collect::<Vec<_>>().as_slice()
+        );
+    }
+
+    #[test]
+    fn test_frame_sequence_iter() {
+        for input in [
+            "1-10",
+            "1-20@3,2-30@4,!10-15",
+            "1-10@b,5-12",
+            "-10..10@b+5,!0-3@b",
+            "20-1@b,3",
+            "80-70@4e,1-3*2",
+            "(1-5,10-15)@3,!4",
+        ] {
+            assert_eq!(
+                parse_frame_sequence(input).unwrap(),
+                frame_sequence_iter(input).unwrap().collect::<Vec<_>>(),
+                "{input}"
+            );
+        }
+
+        let options = ParseOptions {
+            dialect: Dialect::Katana,
+            ..Default::default()
+        };
+        assert_eq!(
+            [1, 4, 7, 10, 2],
+            frame_sequence_iter_with_options("1-10/3,2", &options)
+                .unwrap()
+                .collect::<Vec<_>>()
+                .as_slice()
+        );
+
+        assert!(frame_sequence_iter("1-10@0").is_err());
+        assert!(frame_sequence_iter("1-9223372036854775807@b+1").is_err());
+        assert_eq!(
+            Some(1_000_000_000_000),
+            frame_sequence_iter("1-1000000000000@b").unwrap().nth(1)
+        );
+        // Parts that are excluded entirely are skipped at once.
+        for input in [
+            "1-1000000000000,!1-1000000000000,-5",
+            "1-1000000000000@b,!1-1000000000000,-5",
+            "1-1000000000000@b,!1-1000000000000@2,!0-1000000000000@2,-5",
+        ] {
+            assert_eq!(Some(-5), frame_sequence_iter(input).unwrap().last());
+        }
+        // Binary splitting skips excluded frames one at a time.
+        let input = "1-1000000000000@b,!2-1000000000000";
+        assert!(frame_sequence_iter(input).is_err());
+        let options = ParseOptions {
+            limits: Limits::NONE,
+            ..Default::default()
+        };
+        assert_eq!(
+            [1],
+            frame_sequence_iter_with_options(input, &options)
+                .unwrap()
+                .collect::<Vec<_>>()
+                .as_slice()
+        );
+    }
+}
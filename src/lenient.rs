@@ -0,0 +1,179 @@
+use crate::{limits::check_input_length, parse, FrameSequenceError, ParseOptions};
+use pest::{
+    error::{Error, InputLocation},
+    Position, Span,
+};
+use std::ops::Range;
+
+/// The result of [`parse_frame_sequence_lenient()`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LenientParse {
+    /// The frames of all parts that could be parsed.
+    pub frames: Vec<isize>,
+    /// The problems found in the other parts, in the order they appear in
+    /// the input.
+    pub errors: Vec<FrameSequenceError>,
+}
+
+impl LenientParse {
+    /// Returns `true` if the whole input could be parsed.
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// Parse a frame sequence string, skipping the parts that have errors.
+///
+/// The input is split at every `,` that is not inside parentheses or braces
+/// and each part is checked on its own. All errors are reported, with spans
+/// into `input`, and the frames of the remaining parts are returned as if
+/// the broken parts were not there:
+///
+/// ```
+/// # use frame_sequence::{parse_frame_sequence_lenient, ParseOptions};
+/// let result = parse_frame_sequence_lenient("1-3,20-x,30--,40@", &ParseOptions::default());
+/// assert_eq!([1, 2, 3], result.frames.as_slice());
+/// assert_eq!(
+///     [8..8, 13..13, 16..16],
+///     result
+///         .errors
+///         .iter()
+///         .map(|error| error.span())
+///         .collect::<Vec<_>>()
+///         .as_slice()
+/// );
+/// ```
+pub fn parse_frame_sequence_lenient(input: &str, options: &ParseOptions) -> LenientParse {
+    if let Err(error) = check_input_length(input, &options.limits) {
+        return LenientParse {
+            frames: Vec::new(),
+            errors: vec![error],
+        };
+    }
+
+    let mut parts = Vec::new();
+    let mut exclusions = Vec::new();
+    let mut errors = Vec::new();
+
+    for part in split_parts(input) {
+        let (exclusion, range) = match input[part.clone()].chars().next() {
+            Some(symbol @ ('!' | '^')) => (true, part.start + symbol.len_utf8()..part.end),
+            _ => (false, part.clone()),
+        };
+
+        match parse(&input[range.clone()], options) {
+            Ok(_) if exclusion => exclusions.push(&input[part]),
+            Ok(_) => parts.push(&input[part]),
+            Err(error) => errors.push(relocate(error, input, range.start)),
+        }
+    }
+
+    // Exclusions apply regardless of where they are, so they can be moved
+    // to the end. This way a sequence never starts with one.
+    let frames = if parts.is_empty() {
+        Vec::new()
+    } else {
+        let sequence = parts
+            .into_iter()
+            .chain(exclusions)
+            .collect::<Vec<_>>()
+            .join(",");
+        parse(&sequence, options).unwrap_or_else(|error| {
+            // E.g. a limit exceeded by all parts together.
+            errors.push(relocate_to(error, input, 0..input.len()));
+            Vec::new()
+        })
+    };
+
+    LenientParse { frames, errors }
+}
+
+/// The byte ranges between the top level commas of `input`.
+fn split_parts(input: &str) -> Vec<Range<usize>> {
+    let mut depth = 0usize;
+    let mut start = 0;
+    let mut parts = Vec::new();
+
+    for (index, character) in input.char_indices() {
+        match character {
+            '(' | '{' => depth += 1,
+            ')' | '}' => depth = depth.saturating_sub(1),
+            ',' if 0 == depth => {
+                parts.push(start..index);
+                start = index + 1;
+            }
+            _ => (),
+        }
+    }
+    parts.push(start..input.len());
+
+    parts
+}
+
+/// Moves an error in a part of `input` starting at `offset` to `input`.
+fn relocate(error: FrameSequenceError, input: &str, offset: usize) -> FrameSequenceError {
+    let span = error.span();
+    relocate_to(error, input, span.start + offset..span.end + offset)
+}
+
+fn relocate_to(error: FrameSequenceError, input: &str, span: Range<usize>) -> FrameSequenceError {
+    let suggestion = error.suggestion().map(str::to_string);
+    let kind = error.kind();
+    let pest_error = error.into_pest_error();
+
+    let pest_error = match pest_error.location {
+        InputLocation::Pos(_) => Error::new_from_pos(
+            pest_error.variant,
+            Position::new(input, span.start).unwrap(),
+        ),
+        InputLocation::Span(_) => Error::new_from_span(
+            pest_error.variant,
+            Span::new(input, span.start, span.end).unwrap(),
+        ),
+    };
+
+    let error = FrameSequenceError::new(kind, pest_error);
+    match suggestion {
+        Some(suggestion) => error.with_suggestion(suggestion),
+        None => error,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse_frame_sequence_lenient, FrameSequenceError, ParseOptions};
+
+    #[test]
+    fn test_lenient() {
+        let result =
+            parse_frame_sequence_lenient("1-10@0,5,(1-2,x)r,7-8", &ParseOptions::default());
+        assert_eq!([5, 7, 8], result.frames.as_slice());
+        assert_eq!(2, result.errors.len());
+        assert!(matches!(result.errors[0], FrameSequenceError::ZeroStep(_)));
+        assert_eq!(5..6, result.errors[0].span());
+        assert_eq!(14..14, result.errors[1].span());
+        assert!(result.errors[1]
+            .to_string()
+            .contains("1-10@0,5,(1-2,x)r,7-8"));
+    }
+
+    #[test]
+    fn test_lenient_exclusions() {
+        let options = ParseOptions::default();
+        let result = parse_frame_sequence_lenient("1-5@,!2,1-4^3,!x", &options);
+        assert_eq!([1, 4], result.frames.as_slice());
+        assert_eq!([4..4, 15..15], spans(&result.errors).as_slice());
+
+        let result = parse_frame_sequence_lenient("1-10@{5,1},,", &options);
+        assert_eq!(10, result.frames.len());
+        assert_eq!([11..11, 12..12], spans(&result.errors).as_slice());
+
+        let result = parse_frame_sequence_lenient("1-3,10-12", &options);
+        assert!(result.is_ok());
+        assert_eq!([1, 2, 3, 10, 11, 12], result.frames.as_slice());
+    }
+
+    fn spans(errors: &[FrameSequenceError]) -> Vec<std::ops::Range<usize>> {
+        errors.iter().map(|error| error.span()).collect()
+    }
+}
@@ -19,7 +19,17 @@
 //!
 //! `10-20@2` ⟶ `[10, 12, 14, 16, 18, 20]`
 //!
-//! Step size must be always positive.
+//! Step size must be always positive. A step size of zero is an error.
+//!
+//! Instead of a dash, `..` or `:` can be used to separate the start and end
+//! of a range. This is easier to read when frames are negative:
+//!
+//! `-10..-5` ⟶ `[-10, -9, -8, -7, -6, -5]`
+//!
+//! `-3:-1` ⟶ `[-3, -2, -1]`
+//!
+//! Three or more numbers joined by dashes, e.g. `1-2-3`, are rejected as
+//! ambiguous.
 //!
 //! To get a sequence backwards specify the range in reverse:
 //!
@@ -34,17 +44,296 @@
 //!
 //! `80-70@4` ⟶ `[80, 76, 72]`
 //!
+//! An `e` after the step size always includes the end frame:
+//!
+//! `80-70@4e` ⟶ `[80, 76, 72, 70]`
+//!
+//! To do this for all ranges, parse with
+//! [`ParseOptions::include_last_frame`] set.
+//!
+//! # Symbolic Frames
+//!
+//! With [`parse_frame_sequence_in()`] the symbols `first`, `last` and
+//! `current` can be used instead of frame numbers. They are resolved
+//! against a [`FrameContext`]:
+//!
+//! `first-first+10@5` ⟶ `[1001, 1006, 1011]` (with `first` being `1001`)
+//!
+//! Symbols can also be used in [arithmetic](#arithmetic) expressions.
+//!
+//! # Open-Ended Ranges
+//!
+//! A range without a start runs from `first`, a range without an end runs
+//! to `last` of the [`FrameContext`]:
+//!
+//! `1050-` ⟶ `[1050, 1051, …, 1100]` (with `last` being `1100`)
+//!
+//! `..1020` ⟶ `[1001, 1002, …, 1020]` (with `first` being `1001`)
+//!
+//! `first-` ⟶ all frames of the shot.
+//!
+//! Parsing an open-ended range without the respective frame set in the
+//! context is an error. As `-1020` is the negative frame `-1020`, a range
+//! without a start must use the `..` or `:` separator.
+//!
+//! # Arithmetic
+//!
+//! Frames and step sizes can be integer expressions using `+`, `-`, `*`,
+//! `/` (rounding towards zero) and parentheses:
+//!
+//! `1001+10-(1100-5)@2*2` ⟶ `[1011, 1015, …, 1095]`
+//!
+//! `(first+last)/2` ⟶ the frame in the middle of the shot.
+//!
+//! The first dash of a dash range always separates its start from its end.
+//! Use parentheses or the `..`/`:` range separators to subtract on the left
+//! side: `(last-10)-last` or `last-10..last`. As `last-10` on its own could
+//! be a frame or a range, it is an error: write `(last-10)` for the frame or
+//! `last..10` for the range.
+//!
+//! A `+`, `-` or `*` after the end of a range [shifts](#shifts) or
+//! [holds](#holds) the range. To use them in the end frame, put it in
+//! parentheses. Likewise, a step size can only contain `*` and `/`:
+//! `1-100@(2+3)`.
+//!
+//! Overflows and divisions by zero are reported as errors.
+//!
+//! # Subframes
+//!
+//! [`parse_subframe_sequence()`] accepts fractional frames and step sizes
+//! and returns a [`Vec`]`<`[`f64`]`>`:
+//!
+//! `1-2@0.25` ⟶ `[1.0, 1.25, 1.5, 1.75, 2.0]`
+//!
 //! # Nuke-Style Step Size Token
 //!
 //! Using `x` instead of `@` as the step size token separator also works.
+//!
+//! # Dialects
+//!
+//! Frame sequences copied from other applications can be parsed with
+//! [`parse_frame_sequence_with()`] by specifying the [`Dialect`] they were
+//! written in:
+//!
+//! | Dialect                | Example          |
+//! |------------------------|------------------|
+//! | [`Dialect::Nuke`]      | `1-100x5 120`    |
+//! | [`Dialect::Houdini`]   | `1 100 5`        |
+//! | [`Dialect::Maya`]      | `1:100:5`        |
+//! | [`Dialect::Deadline`]  | `1-100x5,120`    |
+//! | [`Dialect::Rv`]        | `1-100x5#`       |
+//! | [`Dialect::Katana`]    | `1-100/5,120`    |
+//!
+//! When the source of a string is unknown, [`detect_dialect()`] returns the
+//! most likely dialect together with a confidence and any alternatives.
+//!
+//! # Copy-Pasted Input
+//!
+//! Frame lists from spreadsheets, chats or emails often contain spaces,
+//! semicolons, en dashes or trailing commas. With
+//! [`ParseOptions::normalize`] set, such input is accepted:
+//!
+//! `1, 3; 5 – 10,` ⟶ `[1, 3, 5, 6, 7, 8, 9, 10]`
+//!
+//! [`normalize_frame_sequence()`] reports what was changed. Valid input is
+//! never changed. Neither is input that is valid in another
+//! [dialect](#dialects), e.g. `1 100 5`, which is an error instead.
+//!
+//! # Refinement Passes
+//!
+//! A list of step sizes in braces renders the range in several passes,
+//! from coarse to fine. Each pass only adds the frames that no previous
+//! pass produced:
+//!
+//! `1-20@{10,5,1}` ⟶ `[1, 11, 6, 16, 2, 3, 4, 5, 7, …, 20]`
+//!
+//! # Frame Count
+//!
+//! Instead of a step size, `#` followed by a number picks that many frames,
+//! spread as evenly as possible. The start and end frames are always
+//! included:
+//!
+//! `1-100#5` ⟶ `[1, 26, 51, 75, 100]`
+//!
+//! If the range has fewer frames than asked for, all of them are returned.
+//!
+//! # Random Order
+//!
+//! `r` instead of a step size shuffles the frames of a range. With a seed
+//! the order is reproducible, across platforms and versions of this crate:
+//!
+//! `1-5@r42` ⟶ `[2, 3, 5, 1, 4]`
+//!
+//! Without a seed, e.g. `1-5@r`, the order differs on every run.
+//!
+//! # Groups
+//!
+//! Parts can be grouped with parentheses. A step size, binary splitting,
+//! random order, pass list or frame count after a group applies to the
+//! frames of the whole group, in order. An `r` reverses the group:
+//!
+//! `(1-5,10-15)@3` ⟶ `[1, 4, 11, 14]`
+//!
+//! `(1-3,7)r` ⟶ `[7, 3, 2, 1]`
+//!
+//! Groups can be nested, up to 64 levels deep. Parentheses around a single
+//! expression without a range, e.g. `(first+last)`, are
+//! [arithmetic](#arithmetic).
+//!
+//! # Shifts
+//!
+//! `+` or `-` after a range or group moves all its frames, after any step
+//! size, binary splitting etc. was applied:
+//!
+//! `1-3+1000` ⟶ `[1001, 1002, 1003]`
+//!
+//! `(1-2,50)-1000` ⟶ `[-999, -998, -950]`
+//!
+//! `1-5@b+10` ⟶ `[11, 15, 13, 12, 14]`
+//!
+//! A `-` directly after the end or a numeric step size of a dash range could
+//! also be a subtraction, e.g. `1001+10-1100-5` or `1-10@2-3`, and so could
+//! a shift of a dash range in parentheses, e.g. `(5-3)-1`. These are
+//! [ambiguous](#example-frame-sequence-strings) and reported as errors.
+//! Write `1001+10..1100-5` or `(1-10@2)-3` to shift and `1001+10-(1100-5)`
+//! to subtract.
+//!
+//! A `+` or `-` and a number directly after a [symbol](#symbolic-frames)
+//! at the end of a range offsets the symbol: `first-first+10` ends at
+//! `first+10`.
+//!
+//! # Ping-Pong
+//!
+//! `pp` after the step size token plays a range or group forward and then
+//! back. The frames on the way back are kept, even though they repeat:
+//!
+//! `1-4@pp` ⟶ `[1, 2, 3, 4, 3, 2, 1]`
+//!
+//! `1-7@2pp` ⟶ `[1, 3, 5, 7, 5, 3, 1]`
+//!
+//! Frames that were already in an earlier part are removed on the way
+//! forward. The way back is kept in full if the range or group added any
+//! new frame and removed otherwise:
+//!
+//! `1-2,1-4@pp` ⟶ `[1, 2, 3, 4, 3, 2, 1]`
+//!
+//! `1-5,3-1@pp` ⟶ `[1, 2, 3, 4, 5]`
+//!
+//! # Holds
+//!
+//! `*` after a range or group repeats each of its frames, e.g. to animate
+//! on twos:
+//!
+//! `1-3*2` ⟶ `[1, 1, 2, 2, 3, 3]`
+//!
+//! Deliberate holds are kept while duplicates from overlapping parts are
+//! still removed:
+//!
+//! `1-3*2,2-4` ⟶ `[1, 1, 2, 2, 3, 3, 4]`
+//!
+//! As a range can not end in a multiplication, use parentheses to multiply
+//! there: `1-(5*2)`. A step size is an expression, so `1-10@2*2` has a step
+//! size of four. Use a group to hold a stepped range: `(1-10@2)*2`.
+//!
+//! A dash range in parentheses followed by a hold, e.g. `(10-2)*3`, could
+//! also be a product and is an error. Write `(10..2)*3` to hold the range or
+//! `3*(10-2)` to multiply.
+//!
+//! # Exclusions
+//!
+//! Frames can be removed from a sequence by prefixing a part with `!` or
+//! by joining it with `^`:
+//!
+//! `1-10,!4-6` ⟶ `[1, 2, 3, 7, 8, 9, 10]`
+//!
+//! `1-10^2-10@2` ⟶ `[1, 3, 5, 7, 9]`
+//!
+//! Exclusions apply to the whole sequence (or [group](#groups)), regardless
+//! of where they appear in it. The order of the remaining frames is kept.
+//!
+//! # Large Sequences
+//!
+//! [`parse_frame_list()`] returns a [`FrameList`] that stores a sequence as
+//! [`FrameRun`]s, arithmetic progressions like `1-1000000@2`. Sequences of
+//! frames, ranges and exclusions are parsed without expanding their ranges,
+//! so `1-1000000000` takes as little time and memory as `1-10`.
+//!
+//! A [`FrameSet`] holds the same runs in ascending order. Both can be
+//! converted from and to a [`Vec`]`<`[`isize`]`>` or a
+//! [`RangeInclusive`](std::ops::RangeInclusive)`<`[`isize`]`>`.
+//!
+//! To process frames one at a time, [`frame_sequence_iter()`] expands
+//! ranges, including binary splitting, lazily and removes duplicates up
+//! front. Its memory use only depends on the number of parts and runs.
+//!
+//! # Formatting
+//!
+//! [`format_frame_sequence()`] is the inverse of parsing. It turns frames
+//! into the shortest frame sequence string that parses back into them:
+//!
+//! `[1, 2, 3, 5, 7, 9, 20]` ⟶ `1-3,5-9@2,20`
+//!
+//! Frames that no string parses into, e.g. a frame repeated anywhere but
+//! right after itself or on the way back of a ping-pong, give [`None`].
+//!
+//! [`format_frame_sequence_with()`] writes the string in a [`FormatStyle`],
+//! e.g. with `x` step sizes, without backwards ranges or as a plain list of
+//! frames.
+//!
+//! # Limits
+//!
+//! To safely parse untrusted input, the length of the input, the number of
+//! parts and frames and the size of frames are checked against [`Limits`]
+//! before any frames are generated. The defaults allow up to ten million
+//! frames; they can be changed via [`ParseOptions::limits`].
+//!
+//! # Errors
+//!
+//! Errors are reported as a [`FrameSequenceError`]. Its variant tells what
+//! kind of problem was found. It also carries the byte span of the offending
+//! part of the input and, where possible, a suggestion how to fix it.
+//!
+//! [`parse_frame_sequence_lenient()`] reports the errors of all parts at
+//! once and still returns the frames of the parts without errors.
+use dialect::{is_range_rule, is_sequence_rule};
+use error::ErrorKind;
 use itertools::Itertools;
+use limits::{check_frame, check_input_length, check_sequence};
 use pest::{
-    error::Error,
+    error::{Error, ErrorVariant},
     iterators::{Pair, Pairs},
-    Parser,
+    Parser, Position, Span,
 };
 use pest_derive::Parser;
-use std::{cmp::Ordering, collections::HashSet};
+use random::SplitMix64;
+use std::{
+    cmp::Ordering,
+    collections::HashSet,
+    hash::Hash,
+    iter::{once, repeat_n},
+};
+
+mod dialect;
+mod error;
+mod format;
+mod frame_set;
+mod iter;
+mod lenient;
+mod limits;
+mod normalize;
+mod random;
+mod subframe;
+pub use dialect::{detect_dialect, Dialect, DialectDetection};
+pub use error::{ErrorDetails, FrameSequenceError};
+pub use format::{format_frame_sequence, format_frame_sequence_with, FormatStyle};
+pub use frame_set::{parse_frame_list, FrameList, FrameRun, FrameSet};
+pub use iter::{frame_sequence_iter, frame_sequence_iter_with_options, FrameSequenceIter};
+pub use lenient::{parse_frame_sequence_lenient, LenientParse};
+pub use limits::Limits;
+pub use normalize::{
+    normalize_frame_sequence, NormalizationChange, NormalizationKind, NormalizedInput,
+};
+pub use subframe::{parse_subframe_sequence, parse_subframe_sequence_with_options};
 
 #[derive(Parser)]
 #[grammar = "frame_format_grammar.pest"]
@@ -53,10 +342,245 @@ struct FrameSequenceParser;
 /// Parse a frame sequence string into a [`Vec`]`<`[`isize`]`>` of frames.
 ///
 /// See the main page of the documentation for example `input` strings.
-pub fn parse_frame_sequence(input: &str) -> Result<Vec<isize>, Box<Error<Rule>>> {
-    FrameSequenceParser::parse(Rule::FrameSequenceString, input)
-        .map(|token_tree| remove_duplicates(frame_sequence_token_tree_to_frames(token_tree)))
-        .map_err(|e| e.into())
+///
+/// The default [`Limits`] apply.
+pub fn parse_frame_sequence(input: &str) -> Result<Vec<isize>, FrameSequenceError> {
+    parse(input, &ParseOptions::default())
+}
+
+/// Parse a frame sequence string written in the syntax of the given
+/// [`Dialect`] into a [`Vec`]`<`[`isize`]`>` of frames.
+///
+/// ```
+/// # use frame_sequence::{parse_frame_sequence_with, Dialect};
+/// assert_eq!(
+///     parse_frame_sequence_with("1 9 4", Dialect::Houdini).unwrap(),
+///     parse_frame_sequence_with("1:9:4", Dialect::Maya).unwrap()
+/// );
+/// ```
+pub fn parse_frame_sequence_with(
+    input: &str,
+    dialect: Dialect,
+) -> Result<Vec<isize>, FrameSequenceError> {
+    parse(
+        input,
+        &ParseOptions {
+            dialect,
+            ..Default::default()
+        },
+    )
+}
+
+/// The frames of a shot that the symbols `first`, `last` and `current`
+/// resolve to.
+///
+/// Used with [`parse_frame_sequence_in()`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct FrameContext {
+    /// The first frame of the shot, `first`.
+    pub first: Option<isize>,
+    /// The last frame of the shot, `last`.
+    pub last: Option<isize>,
+    /// The current frame, `current`.
+    pub current: Option<isize>,
+}
+
+/// Parse a frame sequence string that may contain the symbols `first`,
+/// `last` and `current` into a [`Vec`]`<`[`isize`]`>` of frames.
+///
+/// The symbols are resolved against `context`. Using a symbol that is
+/// [`None`] in `context` is an error.
+///
+/// ```
+/// # use frame_sequence::{parse_frame_sequence_in, FrameContext};
+/// let context = FrameContext {
+///     first: Some(1001),
+///     last: Some(1010),
+///     current: None,
+/// };
+/// assert_eq!(
+///     [1001, 1003, 1005, 1007, 1009],
+///     parse_frame_sequence_in("first-last@2", &context)
+///         .unwrap()
+///         .as_slice()
+/// );
+/// assert!(parse_frame_sequence_in("current", &context).is_err());
+/// ```
+pub fn parse_frame_sequence_in(
+    input: &str,
+    context: &FrameContext,
+) -> Result<Vec<isize>, FrameSequenceError> {
+    parse(
+        input,
+        &ParseOptions {
+            context: *context,
+            ..Default::default()
+        },
+    )
+}
+
+/// Options for [`parse_frame_sequence_with_options()`].
+///
+/// ```
+/// # use frame_sequence::{parse_frame_sequence_with_options, ParseOptions};
+/// let options = ParseOptions {
+///     include_last_frame: true,
+///     ..Default::default()
+/// };
+/// assert_eq!(
+///     [80, 76, 72, 70],
+///     parse_frame_sequence_with_options("80-70@4", &options)
+///         .unwrap()
+///         .as_slice()
+/// );
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ParseOptions {
+    /// The syntax of the input.
+    pub dialect: Dialect,
+    /// What the symbols `first`, `last` and `current` resolve to.
+    pub context: FrameContext,
+    /// Always include the end frame of stepped ranges, as if every step
+    /// size was followed by an `e`.
+    pub include_last_frame: bool,
+    /// Limits for parsing untrusted input.
+    pub limits: Limits,
+    /// Accept copy-pasted input with e.g. spaces, semicolons or en dashes
+    /// by [normalizing](normalize_frame_sequence) it, if it is not valid as
+    /// is. Only used with [`Dialect::Native`].
+    ///
+    /// Input that is valid in another [`Dialect`], e.g. `1 100 5` in
+    /// [`Dialect::Houdini`], is not normalized, as it likely means other
+    /// frames there. Errors refer to the original input.
+    pub normalize: bool,
+}
+
+/// Parse a frame sequence string into a [`Vec`]`<`[`isize`]`>` of frames
+/// with the given [`ParseOptions`].
+pub fn parse_frame_sequence_with_options(
+    input: &str,
+    options: &ParseOptions,
+) -> Result<Vec<isize>, FrameSequenceError> {
+    parse(input, options)
+}
+
+/// How deep parentheses can be nested. Bounds the recursion when parsing
+/// and expanding a sequence.
+const MAX_NESTING: usize = 64;
+
+fn parse(input: &str, options: &ParseOptions) -> Result<Vec<isize>, FrameSequenceError> {
+    parse_normalized(input, options, parse_as_is)
+}
+
+/// Parses `input` as is and, if that fails and the [`ParseOptions`] ask for
+/// it, normalized.
+fn parse_normalized<T>(
+    input: &str,
+    options: &ParseOptions,
+    parse_as_is: impl Fn(&str, &ParseOptions) -> Result<T, FrameSequenceError>,
+) -> Result<T, FrameSequenceError> {
+    match parse_as_is(input, options) {
+        Err(error) if options.normalize && Dialect::Native == options.dialect => {
+            // E.g. `1 100 5` is a range in Houdini, not three frames.
+            if let Some(dialect) = detect_dialect(input).and_then(|detection| {
+                once(detection.dialect)
+                    .chain(
+                        detection
+                            .alternatives
+                            .into_iter()
+                            .map(|(dialect, _)| dialect),
+                    )
+                    .find(|dialect| Dialect::Native != *dialect)
+            }) {
+                return Err(error.with_suggestion(format!(
+                    "parse it with `Dialect::{dialect:?}`, in which it is valid"
+                )));
+            }
+            let normalized = normalize_frame_sequence(input);
+            if normalized.changes.is_empty() {
+                Err(error)
+            } else {
+                parse_as_is(&normalized.input, options).map_err(|_| error)
+            }
+        }
+        result => result,
+    }
+}
+
+fn parse_as_is(input: &str, options: &ParseOptions) -> Result<Vec<isize>, FrameSequenceError> {
+    check_input_length(input, &options.limits)?;
+    check_nesting(input)?;
+    let token_tree = FrameSequenceParser::parse(options.dialect.rule(), input)?;
+    check_sequence(input, token_tree.clone(), options)?;
+
+    Ok(sequence_to_frames(token_tree, options)?
+        .into_iter()
+        .flat_map(|frame| repeat_n(frame.frame, frame.hold))
+        .collect())
+}
+
+fn check_nesting(input: &str) -> Result<(), FrameSequenceError> {
+    let mut depth = 0usize;
+    for (index, character) in input.char_indices() {
+        match character {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            _ => continue,
+        }
+        if MAX_NESTING < depth {
+            return Err(FrameSequenceError::new(
+                FrameSequenceError::LimitExceeded,
+                Error::new_from_pos(
+                    ErrorVariant::CustomError {
+                        message: format!(
+                            "parentheses can be nested at most {MAX_NESTING} levels deep"
+                        ),
+                    },
+                    Position::new(input, index).unwrap(),
+                ),
+            )
+            .with_suggestion("use fewer nested groups"));
+        }
+    }
+    Ok(())
+}
+
+/// A frame and how many times it is repeated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct HeldFrame {
+    frame: isize,
+    hold: usize,
+    /// A deliberate repetition of an earlier frame, e.g. on the way back
+    /// of a ping-pong. Kept by [`remove_duplicate_frames()`] if its part
+    /// adds any new frame.
+    repeated: bool,
+}
+
+impl From<isize> for HeldFrame {
+    fn from(frame: isize) -> Self {
+        Self {
+            frame,
+            hold: 1,
+            repeated: false,
+        }
+    }
+}
+
+/// Expands a sequence, removes duplicates and applies its exclusions.
+fn sequence_to_frames(
+    pairs: Pairs<Rule>,
+    options: &ParseOptions,
+) -> Result<Vec<HeldFrame>, FrameSequenceError> {
+    let excluded = excluded_frames(pairs.clone(), options)?
+        .into_iter()
+        .collect::<HashSet<_>>();
+
+    Ok(
+        remove_duplicate_frames(frame_sequence_token_tree_to_frames(pairs, options)?)
+            .into_iter()
+            .filter(|frame| !excluded.contains(&frame.frame))
+            .collect(),
+    )
 }
 
 fn chop(seq: &mut Vec<isize>, result: &mut Vec<isize>, elements: usize) {
@@ -66,8 +590,11 @@ fn chop(seq: &mut Vec<isize>, result: &mut Vec<isize>, elements: usize) {
             .tuple_windows()
             .flat_map(|pair: (&isize, &isize)| {
                 let left = *pair.0;
-                let right = (*pair.0 + *pair.1) / 2;
-                if left < right {
+                // Can not overflow, unlike `(left + right) / 2`.
+                let right = ((*pair.0 as i128 + *pair.1 as i128) / 2) as isize;
+                // Rounding towards zero makes the middle of e.g. `-3` and
+                // `-2` the latter, which is not a new frame.
+                if left < right && right < *pair.1 {
                     result.push(right);
                     vec![left, right]
                 } else {
@@ -84,17 +611,18 @@ fn chop(seq: &mut Vec<isize>, result: &mut Vec<isize>, elements: usize) {
 }
 
 pub(crate) fn binary_sequence(range: (isize, isize)) -> Vec<isize> {
+    let elements = range.0.abs_diff(range.1).saturating_add(1);
     match range.0.cmp(&range.1) {
         Ordering::Less => {
             let mut seq = vec![range.0, range.1];
             let mut result = seq.clone();
-            chop(&mut seq, &mut result, (range.1 - range.0) as _);
+            chop(&mut seq, &mut result, elements);
             result
         }
         Ordering::Greater => {
             let mut seq = vec![range.1, range.0];
             let mut result = seq.clone();
-            chop(&mut seq, &mut result, (range.0 - range.1) as _);
+            chop(&mut seq, &mut result, elements);
             result.reverse();
             result
         }
@@ -102,63 +630,537 @@ pub(crate) fn binary_sequence(range: (isize, isize)) -> Vec<isize> {
     }
 }
 
-fn frame_to_number(frame: Pair<Rule>) -> isize {
-    frame.as_str().parse::<isize>().unwrap()
+pub(crate) fn custom_error(kind: ErrorKind, message: String, span: Span) -> FrameSequenceError {
+    FrameSequenceError::new(
+        kind,
+        Error::new_from_span(ErrorVariant::CustomError { message }, span),
+    )
 }
 
-fn frame_sequence_token_tree_to_frames(pairs: Pairs<Rule>) -> Vec<isize> {
-    pairs
-        .into_iter()
-        .flat_map(|pair| {
+/// Evaluates a frame, a frame symbol, a number or an arithmetic expression.
+fn frame_to_number(frame: Pair<Rule>, options: &ParseOptions) -> Result<isize, FrameSequenceError> {
+    let span = frame.as_span();
+    let out_of_range = || {
+        custom_error(
+            FrameSequenceError::Overflow,
+            format!("`{}` is out of range", span.as_str()),
+            span,
+        )
+    };
+
+    match frame.as_rule() {
+        Rule::PositiveNumber => frame.as_str().parse::<isize>().map_err(|_| out_of_range()),
+        Rule::FrameSymbol => match frame.as_str() {
+            "first" => options.context.first,
+            "last" => options.context.last,
+            "current" => options.context.current,
+            _ => unreachable!(),
+        }
+        .ok_or_else(|| {
+            custom_error(
+                FrameSequenceError::UnresolvedSymbol,
+                format!(
+                    "`{}` is used but not set in the frame context",
+                    frame.as_str()
+                ),
+                span,
+            )
+            .with_suggestion(format!("set `{}` in the `FrameContext`", frame.as_str()))
+        }),
+        // A frame as used by the dialects.
+        Rule::Frame => {
+            let mut pairs = frame.into_inner();
+            let pair = pairs.next().unwrap();
             match pair.as_rule() {
-                Rule::FrameSequenceString | Rule::FrameSequence | Rule::FrameSequencePart => {
-                    frame_sequence_token_tree_to_frames(pair.into_inner())
+                Rule::FrameSymbol => {
+                    let value = frame_to_number(pair, options)?;
+                    match pairs.next() {
+                        Some(offset) => value
+                            .checked_add(frame_to_number(
+                                offset.into_inner().next().unwrap(),
+                                options,
+                            )?)
+                            .ok_or_else(out_of_range),
+                        None => Ok(value),
+                    }
                 }
-                Rule::FrameRange => {
-                    let mut pairs = pair.into_inner();
-                    let left = frame_to_number(pairs.next().unwrap());
-                    let right = frame_to_number(pairs.next().unwrap());
-
-                    // Do we have an `@`?
-                    if pairs.next().is_some() {
-                        let pair = pairs.next().unwrap();
-                        match pair.as_rule() {
-                            Rule::PositiveNumber => {
-                                let step = frame_to_number(pair);
-
-                                match left.cmp(&right) {
-                                    Ordering::Less => {
-                                        (left..right + 1).step_by(step as _).collect::<Vec<_>>()
-                                    }
-                                    Ordering::Greater => (right..left + 1)
-                                        .rev()
-                                        .step_by(step as _)
-                                        .collect::<Vec<_>>(),
-                                    Ordering::Equal => vec![left],
-                                }
-                            }
-                            Rule::BinarySequenceSymbol => binary_sequence((left, right)),
-                            _ => unreachable!(),
-                        }
-                    } else if left < right {
-                        (left..right + 1).collect::<Vec<_>>()
-                    } else if right < left {
-                        (right..left + 1).rev().collect::<Vec<_>>()
+                _ => span.as_str().parse::<isize>().map_err(|_| out_of_range()),
+            }
+        }
+        Rule::Factor => {
+            let mut pairs = frame.into_inner();
+            let pair = pairs.next().unwrap();
+            match pair.as_rule() {
+                Rule::Sign => {
+                    let value = frame_to_number(pairs.next().unwrap(), options)?;
+                    if "-" == pair.as_str() {
+                        value.checked_neg().ok_or_else(out_of_range)
+                    } else {
+                        Ok(value)
                     }
-                    // left == right
-                    else {
-                        vec![left]
+                }
+                _ => frame_to_number(pair, options),
+            }
+        }
+        Rule::Expression | Rule::RangeStart | Rule::RangeEnd | Rule::Product => {
+            let mut pairs = frame.into_inner();
+            let mut value = frame_to_number(pairs.next().unwrap(), options)?;
+            while let Some(operator) = pairs.next() {
+                let operand = pairs.next().unwrap();
+                let operand_span = operand.as_span();
+                let operand = frame_to_number(operand, options)?;
+                value = match operator.as_str() {
+                    "+" => value.checked_add(operand),
+                    "-" => value.checked_sub(operand),
+                    "*" => value.checked_mul(operand),
+                    "/" => {
+                        if 0 == operand {
+                            return Err(custom_error(
+                                FrameSequenceError::DivisionByZero,
+                                "division by zero".to_string(),
+                                operand_span,
+                            ));
+                        }
+                        value.checked_div(operand)
                     }
+                    _ => unreachable!(),
+                }
+                .ok_or_else(out_of_range)?;
+            }
+            Ok(value)
+        }
+        _ => unreachable!(),
+    }
+}
+
+/// Expands each part of a sequence.
+fn frame_sequence_token_tree_to_frames(
+    pairs: Pairs<Rule>,
+    options: &ParseOptions,
+) -> Result<Vec<Vec<HeldFrame>>, FrameSequenceError> {
+    pairs
+        .into_iter()
+        .map(|pair| {
+            Ok(match pair.as_rule() {
+                rule if is_sequence_rule(rule) => {
+                    frame_sequence_token_tree_to_frames(pair.into_inner(), options)?
+                }
+                Rule::AmbiguousFrameRange => {
+                    return Err(custom_error(
+                        FrameSequenceError::AmbiguousRange,
+                        format!("ambiguous frame range `{}`", pair.as_str()),
+                        pair.as_span(),
+                    )
+                    .with_suggestion(
+                        "use `..` or `:` to separate the start and end frame, e.g. `1..-2`",
+                    ))
+                }
+                Rule::AmbiguousShift => {
+                    return Err(custom_error(
+                        FrameSequenceError::AmbiguousRange,
+                        format!("ambiguous frame range `{}`", pair.as_str()),
+                        pair.as_span(),
+                    )
+                    .with_suggestion(
+                        "use parentheses to subtract, e.g. `1-(100-5)`, or shift a group, e.g. \
+                         `(1..100)-5`",
+                    ))
+                }
+                Rule::AmbiguousGroup => {
+                    return Err(custom_error(
+                        FrameSequenceError::AmbiguousRange,
+                        format!("ambiguous frame range `{}`", pair.as_str()),
+                        pair.as_span(),
+                    )
+                    .with_suggestion(
+                        "use `..` or `:` for the range in the group, e.g. `(10..2)*3` or \
+                         `(5..3)-1`, or rewrite the arithmetic, e.g. `3*(10-2)` or `(5-3)..1`",
+                    ))
+                }
+                Rule::AmbiguousSymbolRange => {
+                    return Err(custom_error(
+                        FrameSequenceError::AmbiguousRange,
+                        format!("ambiguous frame range `{}`", pair.as_str()),
+                        pair.as_span(),
+                    )
+                    .with_suggestion(
+                        "use parentheses for a single frame, e.g. `(last-10)`, or `..` or `:` \
+                         for a range, e.g. `last..10`",
+                    ))
+                }
+                rule if is_range_rule(rule) => {
+                    let frames = frame_range_to_frames(pair.clone(), options)?
+                        .into_iter()
+                        .map(HeldFrame::from)
+                        .collect();
+                    vec![part_modifiers_to_frames(&pair, frames, options)?]
+                }
+                Rule::Group => {
+                    let frames = group_to_frames(pair.clone(), options)?;
+                    vec![part_modifiers_to_frames(&pair, frames, options)?]
+                }
+                Rule::Frame | Rule::Expression => {
+                    vec![vec![frame_to_number(pair, options)?.into()]]
                 }
-                Rule::Frame => vec![frame_to_number(pair)],
                 _ => vec![],
+            })
+        })
+        .flatten_ok()
+        .collect()
+}
+
+fn frame_range_to_frames(
+    pair: Pair<Rule>,
+    options: &ParseOptions,
+) -> Result<Vec<isize>, FrameSequenceError> {
+    let span = pair.as_span();
+    let mut pairs = pair.into_inner();
+    let left = range_bound(pairs.next().unwrap(), span, options)?;
+    let right = range_bound(pairs.next().unwrap(), span, options)?;
+
+    let include_last = include_last_frame(&pairs, options);
+
+    // Do we have a step?
+    range_modifier_to_frames(
+        left,
+        right,
+        pairs.find(is_range_modifier),
+        include_last,
+        options,
+    )
+}
+
+/// Evaluates the start or end of a range. A missing start or end resolves
+/// to the first or last frame of the [`FrameContext`].
+fn range_bound(
+    bound: Pair<Rule>,
+    range: Span,
+    options: &ParseOptions,
+) -> Result<isize, FrameSequenceError> {
+    let (frame, symbol, bound) = match bound.as_rule() {
+        Rule::OpenRangeStart => (options.context.first, "first", "a start"),
+        Rule::OpenRangeEnd => (options.context.last, "last", "an end"),
+        _ => return frame_to_number(bound, options),
+    };
+
+    frame.ok_or_else(|| {
+        custom_error(
+            FrameSequenceError::UnresolvedSymbol,
+            format!(
+                "open-ended range `{}` needs `{symbol}` to be set in the frame context",
+                range.as_str()
+            ),
+            range,
+        )
+        .with_suggestion(format!(
+            "set `{symbol}` in the `FrameContext` or give the range {bound}"
+        ))
+    })
+}
+
+fn is_range_modifier(pair: &Pair<Rule>) -> bool {
+    matches!(
+        pair.as_rule(),
+        Rule::PositiveNumber
+            | Rule::Product
+            | Rule::BinarySequenceSymbol
+            | Rule::RandomSymbol
+            | Rule::PassList
+            | Rule::FrameCount
+            | Rule::ReverseSymbol
+    )
+}
+
+/// Whether the end frame of a range or group must be included, either by
+/// an explicit `e` after its step size or by the [`ParseOptions`].
+fn include_last_frame(pairs: &Pairs<Rule>, options: &ParseOptions) -> bool {
+    options.include_last_frame
+        || pairs
+            .clone()
+            .any(|pair| Rule::IncludeLastSymbol == pair.as_rule())
+}
+
+/// Applies the ping-pong, shift and hold of a range or group to its frames.
+fn part_modifiers_to_frames(
+    pair: &Pair<Rule>,
+    frames: Vec<HeldFrame>,
+    options: &ParseOptions,
+) -> Result<Vec<HeldFrame>, FrameSequenceError> {
+    let hold = hold(pair)?;
+    let shift = shift(pair, options)?;
+
+    let frames = if pair
+        .clone()
+        .into_inner()
+        .any(|pair| Rule::PingPongSymbol == pair.as_rule())
+    {
+        ping_pong(frames)
+    } else {
+        frames
+    };
+
+    frames
+        .into_iter()
+        .map(|frame| {
+            Ok(HeldFrame {
+                frame: check_frame(shift_frame(frame.frame, shift)?, shift.1, options)?,
+                hold: frame.hold.saturating_mul(hold),
+                ..frame
+            })
+        })
+        .collect()
+}
+
+/// Appends the frames in reverse, without repeating the last one.
+fn ping_pong(frames: Vec<HeldFrame>) -> Vec<HeldFrame> {
+    let back = frames
+        .iter()
+        .rev()
+        .skip(1)
+        .map(|frame| HeldFrame {
+            repeated: true,
+            ..*frame
+        })
+        .collect::<Vec<_>>();
+
+    frames.into_iter().chain(back).collect()
+}
+
+/// The shift of a range or group and the span to report overflows at.
+fn shift<'a>(
+    pair: &Pair<'a, Rule>,
+    options: &ParseOptions,
+) -> Result<(isize, Span<'a>), FrameSequenceError> {
+    match pair
+        .clone()
+        .into_inner()
+        .find(|pair| Rule::Shift == pair.as_rule())
+    {
+        Some(shift) => {
+            let span = shift.as_span();
+            let mut pairs = shift.into_inner();
+            let sign = pairs.next().unwrap();
+            let offset = frame_to_number(pairs.next().unwrap(), options)?;
+            let offset = if "-" == sign.as_str() {
+                offset.checked_neg().ok_or_else(|| {
+                    custom_error(
+                        FrameSequenceError::Overflow,
+                        format!("shift `{}` is out of range", span.as_str()),
+                        span,
+                    )
+                })?
+            } else {
+                offset
+            };
+            Ok((offset, span))
+        }
+        None => Ok((0, pair.as_span())),
+    }
+}
+
+fn shift_frame(frame: isize, (offset, span): (isize, Span)) -> Result<isize, FrameSequenceError> {
+    frame.checked_add(offset).ok_or_else(|| {
+        custom_error(
+            FrameSequenceError::Overflow,
+            format!("frame `{frame}` shifted by `{offset}` is out of range"),
+            span,
+        )
+    })
+}
+
+/// How many times each frame of a range or group is repeated.
+fn hold(pair: &Pair<Rule>) -> Result<usize, FrameSequenceError> {
+    match pair
+        .clone()
+        .into_inner()
+        .find(|pair| Rule::Hold == pair.as_rule())
+    {
+        Some(hold) => {
+            let number = hold.into_inner().next().unwrap();
+            match number.as_str().parse::<usize>() {
+                Ok(hold) if 0 < hold => Ok(hold),
+                _ => Err(custom_error(
+                    FrameSequenceError::ZeroCount,
+                    format!(
+                        "hold `{}` must be greater than zero and in range",
+                        number.as_str()
+                    ),
+                    number.as_span(),
+                )
+                .with_suggestion("use a hold of at least `1`")),
+            }
+        }
+        None => Ok(1),
+    }
+}
+
+/// Expands the range from `left` to `right` with an optional step,
+/// binary splitting, random order, pass list or frame count.
+///
+/// With `include_last`, `right` is appended if the modifier skipped it.
+fn range_modifier_to_frames(
+    left: isize,
+    right: isize,
+    modifier: Option<Pair<Rule>>,
+    include_last: bool,
+    options: &ParseOptions,
+) -> Result<Vec<isize>, FrameSequenceError> {
+    let mut frames = match modifier {
+        Some(pair) => match pair.as_rule() {
+            Rule::PositiveNumber | Rule::Product => {
+                stepped_range(left, right, step_to_number(pair, options)?)
+            }
+            Rule::BinarySequenceSymbol => binary_sequence((left, right)),
+            Rule::RandomSymbol => {
+                let mut rng = match pair.into_inner().next() {
+                    Some(seed) => SplitMix64::new(seed.as_str().parse::<u64>().map_err(|_| {
+                        custom_error(
+                            FrameSequenceError::Overflow,
+                            format!("seed `{}` is out of range", seed.as_str()),
+                            seed.as_span(),
+                        )
+                    })?),
+                    None => SplitMix64::from_entropy(),
+                };
+                let mut frames = stepped_range(left, right, 1);
+                rng.shuffle(&mut frames);
+                frames
+            }
+            Rule::PassList => {
+                let passes = pair
+                    .into_inner()
+                    .map(|step| {
+                        let step = step_to_number(step, options)?;
+                        Ok(stepped_range(left, right, step))
+                    })
+                    .flatten_ok()
+                    .collect::<Result<Vec<_>, FrameSequenceError>>()?;
+                remove_duplicates(passes)
+            }
+            Rule::FrameCount => {
+                let count = pair.into_inner().next().unwrap();
+                let span = count.as_span();
+                match frame_to_number(count, options)? {
+                    count if 0 < count => spread_range(left, right, count as _),
+                    _ => {
+                        return Err(custom_error(
+                            FrameSequenceError::ZeroCount,
+                            "frame count must be greater than zero".to_string(),
+                            span,
+                        )
+                        .with_suggestion("use a frame count of at least `1`"))
+                    }
+                }
+            }
+            _ => unreachable!(),
+        },
+        None => stepped_range(left, right, 1),
+    };
+
+    if include_last && !frames.contains(&right) {
+        frames.push(right);
+    }
+
+    Ok(frames)
+}
+
+fn group_to_frames(
+    pair: Pair<Rule>,
+    options: &ParseOptions,
+) -> Result<Vec<HeldFrame>, FrameSequenceError> {
+    let mut pairs = pair.into_inner();
+    let frames = sequence_to_frames(pairs.next().unwrap().into_inner(), options)?;
+    let include_last = include_last_frame(&pairs, options);
+
+    Ok(match pairs.find(is_range_modifier) {
+        _ if frames.is_empty() => frames,
+        Some(pair) if Rule::ReverseSymbol == pair.as_rule() => frames.into_iter().rev().collect(),
+        // Apply the modifier to the indices of the frames.
+        modifier => range_modifier_to_frames(
+            0,
+            frames.len() as isize - 1,
+            modifier,
+            include_last,
+            options,
+        )?
+        .into_iter()
+        .map(|index| frames[index as usize])
+        .collect(),
+    })
+}
+
+fn step_to_number(step: Pair<Rule>, options: &ParseOptions) -> Result<usize, FrameSequenceError> {
+    let span = step.as_span();
+    match frame_to_number(step, options)? {
+        step if 0 < step => Ok(step as _),
+        _ => Err(custom_error(
+            FrameSequenceError::ZeroStep,
+            "step size must be greater than zero".to_string(),
+            span,
+        )
+        .with_suggestion("use a step size of at least `1`")),
+    }
+}
+
+fn stepped_range(left: isize, right: isize, step: usize) -> Vec<isize> {
+    match left.cmp(&right) {
+        Ordering::Less => (left..=right).step_by(step).collect::<Vec<_>>(),
+        Ordering::Greater => (right..=left).rev().step_by(step).collect::<Vec<_>>(),
+        Ordering::Equal => vec![left],
+    }
+}
+
+/// Picks `count` frames, including `left` and `right`, spread as evenly as
+/// possible.
+fn spread_range(left: isize, right: isize, count: usize) -> Vec<isize> {
+    let distance = left.abs_diff(right) as u128;
+    let intervals = count as u128 - 1;
+
+    if 0 == intervals {
+        vec![left]
+    } else if distance < intervals {
+        stepped_range(left, right, 1)
+    } else {
+        (0..count as u128)
+            .map(|i| {
+                // Rounded to the nearest frame.
+                let offset = ((i * distance + intervals / 2) / intervals) as i128;
+                // The offset may not fit an `isize` but the frame always does.
+                if left < right {
+                    (left as i128 + offset) as isize
+                } else {
+                    (left as i128 - offset) as isize
+                }
+            })
+            .collect()
+    }
+}
+
+fn excluded_frames(
+    pairs: Pairs<Rule>,
+    options: &ParseOptions,
+) -> Result<Vec<isize>, FrameSequenceError> {
+    pairs
+        .into_iter()
+        .map(|pair| match pair.as_rule() {
+            Rule::FrameSequenceString | Rule::FrameSequence => {
+                excluded_frames(pair.into_inner(), options)
             }
+            Rule::Exclusion => Ok(
+                frame_sequence_token_tree_to_frames(pair.into_inner(), options)?
+                    .into_iter()
+                    .flatten()
+                    .map(|frame| frame.frame)
+                    .collect(),
+            ),
+            _ => Ok(vec![]),
         })
-        .collect::<Vec<_>>()
+        .flatten_ok()
+        .collect()
 }
 
-fn remove_duplicates(elements: Vec<isize>) -> Vec<isize> {
-    let mut set = HashSet::<isize>::new();
+fn remove_duplicates<T: Copy + Eq + Hash>(elements: Vec<T>) -> Vec<T> {
+    let mut set = HashSet::<T>::new();
     elements
         .iter()
         .filter_map(|e| {
@@ -172,6 +1174,28 @@ fn remove_duplicates(elements: Vec<isize>) -> Vec<isize> {
         .collect()
 }
 
+/// Joins the parts of a sequence and removes frames that were seen before.
+/// The deliberately [`repeated`](HeldFrame::repeated) frames of a part are
+/// kept if the part added any new frame. The hold of a frame's first
+/// occurrence is kept.
+fn remove_duplicate_frames(parts: Vec<Vec<HeldFrame>>) -> Vec<HeldFrame> {
+    let mut seen = HashSet::<isize>::new();
+    parts
+        .into_iter()
+        .flat_map(|part| {
+            let is_new = part
+                .iter()
+                .map(|frame| !frame.repeated && seen.insert(frame.frame))
+                .collect::<Vec<_>>();
+            let adds_frames = is_new.contains(&true);
+            part.into_iter()
+                .zip(is_new)
+                .filter(move |(frame, is_new)| *is_new || frame.repeated && adds_frames)
+                .map(|(frame, _)| frame)
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     #[test]
@@ -212,6 +1236,16 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_binary_frame_sequence_all_frames() {
+        use crate::parse_frame_sequence;
+        let frames = parse_frame_sequence("0-5@b").unwrap();
+        assert_eq!([0, 5, 2, 1, 3, 4], frames.as_slice());
+        // Backwards, the frames are in the reverse order of forwards.
+        let frames = parse_frame_sequence("-37..-40@b").unwrap();
+        assert_eq!([-39, -38, -37, -40], frames.as_slice());
+    }
+
     #[test]
     fn test_multi_sequence() {
         use crate::parse_frame_sequence;
@@ -225,4 +1259,371 @@ mod tests {
         let frames = parse_frame_sequence("10-20x2,42-33x3").unwrap();
         assert_eq!([10, 12, 14, 16, 18, 20, 42, 39, 36, 33], frames.as_slice());
     }
+
+    #[test]
+    fn test_exclusion() {
+        use crate::parse_frame_sequence;
+        let frames = parse_frame_sequence("1-10,!4-6").unwrap();
+        assert_eq!([1, 2, 3, 7, 8, 9, 10], frames.as_slice());
+    }
+
+    #[test]
+    fn test_exclusion_caret() {
+        use crate::parse_frame_sequence;
+        let frames = parse_frame_sequence("1-10^2-10@2").unwrap();
+        assert_eq!([1, 3, 5, 7, 9], frames.as_slice());
+    }
+
+    #[test]
+    fn test_exclusion_binary() {
+        use crate::parse_frame_sequence;
+        let frames = parse_frame_sequence("10-20@b,!15,!11-12").unwrap();
+        assert_eq!([10, 20, 17, 13, 16, 18, 14, 19], frames.as_slice());
+    }
+
+    #[test]
+    fn test_range_separators() {
+        use crate::parse_frame_sequence;
+        let frames = parse_frame_sequence("-10..-5").unwrap();
+        assert_eq!([-10, -9, -8, -7, -6, -5], frames.as_slice());
+        let frames = parse_frame_sequence("-3:-1,5..1@2").unwrap();
+        assert_eq!([-3, -2, -1, 5, 3, 1], frames.as_slice());
+        assert_eq!(
+            parse_frame_sequence("-10--5").unwrap(),
+            parse_frame_sequence("-10..-5").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_ambiguous_range() {
+        use crate::parse_frame_sequence;
+        let error = parse_frame_sequence("1,1-2-3").unwrap_err();
+        assert!(error.to_string().contains("ambiguous frame range `1-2-3`"));
+    }
+
+    #[test]
+    fn test_frame_symbols() {
+        use crate::{parse_frame_sequence_in, FrameContext};
+        let context = FrameContext {
+            first: Some(1001),
+            last: Some(1100),
+            current: Some(1050),
+        };
+        let frames = parse_frame_sequence_in("first-first+3,current,last", &context).unwrap();
+        assert_eq!([1001, 1002, 1003, 1004, 1050, 1100], frames.as_slice());
+        let frames = parse_frame_sequence_in("last-first@33", &context).unwrap();
+        assert_eq!([1100, 1067, 1034, 1001], frames.as_slice());
+    }
+
+    #[test]
+    fn test_unresolved_frame_symbol() {
+        use crate::{parse_frame_sequence, parse_frame_sequence_in, FrameContext};
+        let context = FrameContext {
+            first: Some(1),
+            ..Default::default()
+        };
+        let error = parse_frame_sequence_in("first-last", &context).unwrap_err();
+        assert!(error.to_string().contains("`last` is used but not set"));
+        assert!(parse_frame_sequence("first").is_err());
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        use crate::{parse_frame_sequence, FrameSequenceError};
+        let frames = parse_frame_sequence("1001+10-(1100-5)@2*2").unwrap();
+        assert_eq!(frames.first(), Some(&1011));
+        assert_eq!(frames.last(), Some(&1095));
+        assert_eq!(frames.len(), 22);
+        let frames = parse_frame_sequence("3*(10-2),-2*-3,7/2,-7/2,2-(1-4)").unwrap();
+        assert_eq!([24, 6, 3, -3, 2, 1, 0, -1, -2], frames.as_slice());
+        let error = parse_frame_sequence("(10-2)*3,-2*-3,7/2,-7/2,2-(1-4)").unwrap_err();
+        assert!(matches!(error, FrameSequenceError::AmbiguousRange(_)));
+        assert!(error.suggestion().unwrap().contains("3*(10-2)"));
+        for input in [
+            "1001+10-1100-5@2*2",
+            "1-10@2-3",
+            "1..10#3-1",
+            "-1-(2*3)-4/2",
+        ] {
+            let error = parse_frame_sequence(input).unwrap_err();
+            assert!(
+                matches!(error, FrameSequenceError::AmbiguousRange(_)),
+                "{input}: {error:?}"
+            );
+            assert!(error.suggestion().unwrap().contains("1-(100-5)"));
+        }
+    }
+
+    #[test]
+    fn test_arithmetic_with_symbols() {
+        use crate::{parse_frame_sequence_in, FrameContext, FrameSequenceError};
+        let context = FrameContext {
+            first: Some(1001),
+            last: Some(1100),
+            current: None,
+        };
+        let frames = parse_frame_sequence_in("(first+last)/2,last-10..last-8", &context).unwrap();
+        assert_eq!([1050, 1090, 1091, 1092], frames.as_slice());
+        let frames = parse_frame_sequence_in("(last-10),(first+2-1000)", &context).unwrap();
+        assert_eq!([1090, 3], frames.as_slice());
+        let frames = parse_frame_sequence_in("(last-10)-last@5", &context).unwrap();
+        assert_eq!([1090, 1095, 1100], frames.as_slice());
+        for input in ["last-10", "first+2-1000@2", "1-2,current-1"] {
+            let error = parse_frame_sequence_in(input, &context).unwrap_err();
+            assert!(
+                matches!(error, FrameSequenceError::AmbiguousRange(_)),
+                "{input}: {error:?}"
+            );
+            assert!(error.suggestion().unwrap().contains("(last-10)"));
+        }
+        assert_eq!(
+            [1100, 1099, 1098],
+            parse_frame_sequence_in("last..1098", &context)
+                .unwrap()
+                .as_slice()
+        );
+    }
+
+    #[test]
+    fn test_arithmetic_errors() {
+        use crate::parse_frame_sequence;
+        let error = parse_frame_sequence("1-10/(5-5)").unwrap_err();
+        assert!(error.to_string().contains("division by zero"));
+        let error = parse_frame_sequence("9223372036854775807+1").unwrap_err();
+        assert!(error.to_string().contains("out of range"));
+        let error = parse_frame_sequence("1-10@(2-3)").unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("step size must be greater than zero"));
+    }
+
+    #[test]
+    fn test_zero_step() {
+        use crate::{parse_frame_sequence, parse_frame_sequence_with, Dialect};
+        for input in ["10-20@0", "10-20x0", "10-20@{2,0}", "(1-3)@0"] {
+            let error = parse_frame_sequence(input).unwrap_err();
+            assert!(error
+                .to_string()
+                .contains("step size must be greater than zero"));
+        }
+        assert!(parse_frame_sequence_with("1 10 0", Dialect::Houdini).is_err());
+        assert!(parse_frame_sequence_with("1:10:0", Dialect::Maya).is_err());
+    }
+
+    #[test]
+    fn test_extreme_frames() {
+        use crate::parse_frame_sequence;
+        const MAX: isize = isize::MAX;
+        let frames = parse_frame_sequence("9223372036854775806-9223372036854775807").unwrap();
+        assert_eq!([MAX - 1, MAX], frames.as_slice());
+        let frames = parse_frame_sequence("9223372036854775807-9223372036854775805@b").unwrap();
+        assert_eq!([MAX - 1, MAX, MAX - 2], frames.as_slice());
+        let frames = parse_frame_sequence("-9223372036854775807..9223372036854775807#3").unwrap();
+        assert_eq!([-MAX, 0, MAX], frames.as_slice());
+        let frames = parse_frame_sequence("9223372036854775807..-9223372036854775807#3").unwrap();
+        assert_eq!([MAX, 0, -MAX], frames.as_slice());
+        for input in [
+            "9223372036854775808",
+            "-9223372036854775807-2..0",
+            "1-9223372036854775808",
+            "1-2@r18446744073709551616",
+            "1-2*18446744073709551616",
+            "9223372036854775807+1",
+            "(1..2)+9223372036854775807",
+        ] {
+            assert!(parse_frame_sequence(input).is_err(), "{input}");
+        }
+    }
+
+    #[test]
+    fn test_nesting() {
+        use crate::parse_frame_sequence;
+        let nested = |depth| format!("{}1-2@2{}", "(".repeat(depth), ")".repeat(depth));
+        assert_eq!([1], parse_frame_sequence(&nested(64)).unwrap().as_slice());
+        assert!(parse_frame_sequence(&nested(65)).is_err());
+    }
+
+    #[test]
+    fn test_pass_list() {
+        use crate::parse_frame_sequence;
+        let frames = parse_frame_sequence("1-20@{10,5,1}").unwrap();
+        assert_eq!(
+            [1, 11, 6, 16, 2, 3, 4, 5, 7, 8, 9, 10, 12, 13, 14, 15, 17, 18, 19, 20],
+            frames.as_slice()
+        );
+        let frames = parse_frame_sequence("10-0x{5,2}").unwrap();
+        assert_eq!([10, 5, 0, 8, 6, 4, 2], frames.as_slice());
+        assert!(parse_frame_sequence("1-20@{10,0}").is_err());
+    }
+
+    #[test]
+    fn test_random() {
+        use crate::parse_frame_sequence;
+        let frames = parse_frame_sequence("1-5@r42").unwrap();
+        assert_eq!([2, 3, 5, 1, 4], frames.as_slice());
+
+        let mut frames = parse_frame_sequence("1-100@r").unwrap();
+        frames.sort();
+        assert_eq!((1..=100).collect::<Vec<_>>(), frames);
+    }
+
+    #[test]
+    fn test_frame_count() {
+        use crate::parse_frame_sequence;
+        let frames = parse_frame_sequence("1-100#5").unwrap();
+        assert_eq!([1, 26, 51, 75, 100], frames.as_slice());
+        let frames = parse_frame_sequence("100-1#5").unwrap();
+        assert_eq!([100, 75, 50, 26, 1], frames.as_slice());
+        let frames = parse_frame_sequence("1-100#12").unwrap();
+        assert_eq!(12, frames.len());
+        assert_eq!((Some(&1), Some(&100)), (frames.first(), frames.last()));
+        let frames = parse_frame_sequence("1-3#5,10-20#1").unwrap();
+        assert_eq!([1, 2, 3, 10], frames.as_slice());
+        assert!(parse_frame_sequence("1-100#0").is_err());
+    }
+
+    #[test]
+    fn test_groups() {
+        use crate::parse_frame_sequence;
+        let frames = parse_frame_sequence("(1-5,10-15)@3").unwrap();
+        assert_eq!([1, 4, 11, 14], frames.as_slice());
+        let frames = parse_frame_sequence("(1-3,7)r,(1-10)").unwrap();
+        assert_eq!([7, 3, 2, 1, 4, 5, 6, 8, 9, 10], frames.as_slice());
+        let frames = parse_frame_sequence("(1-3,11-13)@b").unwrap();
+        assert_eq!([1, 13, 3, 2, 11, 12], frames.as_slice());
+        let frames = parse_frame_sequence("((1-4,!2)r,10)#3").unwrap();
+        assert_eq!([4, 1, 10], frames.as_slice());
+    }
+
+    #[test]
+    fn test_group_or_arithmetic() {
+        use crate::{parse_frame_sequence, FrameSequenceError};
+        let frames = parse_frame_sequence("(2+4)/2,(5)").unwrap();
+        assert_eq!([3, 5], frames.as_slice());
+        let frames = parse_frame_sequence("(5..3)*1,(2+1)*4,4*(3-1)").unwrap();
+        assert_eq!([5, 4, 3, 12, 8], frames.as_slice());
+        let frames = parse_frame_sequence("(5-3)..1,(5..3)-1").unwrap();
+        assert_eq!([2, 1, 4, 3], frames.as_slice());
+        for input in ["(5-3)-1", "(5-3)+1*2", "(5-3)-1,(2-1)*4", "(first-last)*2"] {
+            let error = parse_frame_sequence(input).unwrap_err();
+            assert!(
+                matches!(error, FrameSequenceError::AmbiguousRange(_)),
+                "{input}: {error:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_hold() {
+        use crate::parse_frame_sequence;
+        let frames = parse_frame_sequence("1-3*2,2-4").unwrap();
+        assert_eq!([1, 1, 2, 2, 3, 3, 4], frames.as_slice());
+        let frames = parse_frame_sequence("(1-4@2,2)*2,1-(1*2)").unwrap();
+        assert_eq!([1, 1, 3, 3, 2, 2], frames.as_slice());
+        let frames = parse_frame_sequence("((5,6)*2,7)*2,!6").unwrap();
+        assert_eq!([5, 5, 5, 5, 7, 7], frames.as_slice());
+        assert!(parse_frame_sequence("1-3*0").is_err());
+    }
+
+    #[test]
+    fn test_shift() {
+        use crate::parse_frame_sequence;
+        let frames = parse_frame_sequence("1-3+1000,(1-2,50)-1000").unwrap();
+        assert_eq!([1001, 1002, 1003, -999, -998, -950], frames.as_slice());
+        let frames = parse_frame_sequence("1-5@b+10").unwrap();
+        assert_eq!([11, 15, 13, 12, 14], frames.as_slice());
+        let frames = parse_frame_sequence("1..3-1*2,10-12@2+(2*5)").unwrap();
+        assert_eq!([0, 0, 1, 1, 2, 2, 20, 22], frames.as_slice());
+        assert!(parse_frame_sequence("1-2+9223372036854775807").is_err());
+    }
+
+    #[test]
+    fn test_open_ended_ranges() {
+        use crate::{parse_frame_sequence_in, FrameContext};
+        let context = FrameContext {
+            first: Some(1001),
+            last: Some(1005),
+            current: None,
+        };
+        let frames = parse_frame_sequence_in("1003-", &context).unwrap();
+        assert_eq!([1003, 1004, 1005], frames.as_slice());
+        let frames = parse_frame_sequence_in("..1002,1004:@1e", &context).unwrap();
+        assert_eq!([1001, 1002, 1004, 1005], frames.as_slice());
+        let frames = parse_frame_sequence_in("first-@2,!1003", &context).unwrap();
+        assert_eq!([1001, 1005], frames.as_slice());
+        let frames = parse_frame_sequence_in(":", &context).unwrap();
+        assert_eq!([1001, 1002, 1003, 1004, 1005], frames.as_slice());
+    }
+
+    #[test]
+    fn test_open_ended_ranges_without_context() {
+        use crate::{parse_frame_sequence, parse_frame_sequence_in, FrameContext};
+        let error = parse_frame_sequence("1050-").unwrap_err();
+        assert!(error.to_string().contains("`last`"));
+        let context = FrameContext {
+            last: Some(1100),
+            ..Default::default()
+        };
+        let error = parse_frame_sequence_in("..1020", &context).unwrap_err();
+        assert_eq!(
+            Some("set `first` in the `FrameContext` or give the range a start"),
+            error.suggestion()
+        );
+        assert_eq!(
+            [-1020],
+            parse_frame_sequence_in("-1020", &context)
+                .unwrap()
+                .as_slice()
+        );
+    }
+
+    #[test]
+    fn test_include_last_frame() {
+        use crate::parse_frame_sequence;
+        let frames = parse_frame_sequence("80-70@4e").unwrap();
+        assert_eq!([80, 76, 72, 70], frames.as_slice());
+        let frames = parse_frame_sequence("1-10@3e,20").unwrap();
+        assert_eq!([1, 4, 7, 10, 20], frames.as_slice());
+        let frames = parse_frame_sequence("1-9@4epp").unwrap();
+        assert_eq!([1, 5, 9, 5, 1], frames.as_slice());
+        let frames = parse_frame_sequence("(1-4,8)@3e").unwrap();
+        assert_eq!([1, 4, 8], frames.as_slice());
+        // A `!` after the step size is always an exclusion.
+        let frames = parse_frame_sequence("1-10@4!5").unwrap();
+        assert_eq!([1, 9], frames.as_slice());
+        assert!(parse_frame_sequence("1-10@4!").is_err());
+    }
+
+    #[test]
+    fn test_include_last_frame_option() {
+        use crate::{parse_frame_sequence_with_options, Dialect, ParseOptions};
+        let options = ParseOptions {
+            dialect: Dialect::Nuke,
+            include_last_frame: true,
+            ..Default::default()
+        };
+        let frames = parse_frame_sequence_with_options("1-10x4 20", &options).unwrap();
+        assert_eq!([1, 5, 9, 10, 20], frames.as_slice());
+    }
+
+    #[test]
+    fn test_ping_pong() {
+        use crate::parse_frame_sequence;
+        let frames = parse_frame_sequence("1-4@pp").unwrap();
+        assert_eq!([1, 2, 3, 4, 3, 2, 1], frames.as_slice());
+        let frames = parse_frame_sequence("1-7@2pp,3").unwrap();
+        assert_eq!([1, 3, 5, 7, 5, 3, 1], frames.as_slice());
+        let frames = parse_frame_sequence("(1-2,5)@pp+10*2,!14").unwrap();
+        assert_eq!([11, 11, 12, 12, 15, 15, 12, 12, 11, 11], frames.as_slice());
+        let frames = parse_frame_sequence("3-1@bpp").unwrap();
+        assert_eq!([2, 3, 1, 3, 2], frames.as_slice());
+        // The way back of a ping-pong whose frames were all seen before is
+        // removed with them, otherwise it is kept in full.
+        let frames = parse_frame_sequence("1-3@pp,1-3@pp").unwrap();
+        assert_eq!([1, 2, 3, 2, 1], frames.as_slice());
+        let frames = parse_frame_sequence("1-5,3-1@pp").unwrap();
+        assert_eq!([1, 2, 3, 4, 5], frames.as_slice());
+        let frames = parse_frame_sequence("1-2,1-4@pp").unwrap();
+        assert_eq!([1, 2, 3, 4, 3, 2, 1], frames.as_slice());
+    }
 }
@@ -0,0 +1,357 @@
+use crate::{
+    custom_error,
+    dialect::{is_dialect_sequence_rule, is_range_rule, is_sequence_rule},
+    frame_to_number, hold, include_last_frame, is_range_modifier, range_bound, step_to_number,
+    FrameSequenceError, ParseOptions, Rule,
+};
+use pest::{
+    error::{Error, ErrorVariant},
+    iterators::{Pair, Pairs},
+    Position, Span,
+};
+
+/// Limits for parsing untrusted input.
+///
+/// All limits are checked before any frames are generated, so a string like
+/// `1-9999999999` fails fast instead of exhausting memory.
+///
+/// The defaults are generous enough for any real shot. Use [`Limits::NONE`]
+/// to turn all limits off.
+///
+/// ```
+/// # use frame_sequence::{parse_frame_sequence_with_options, Limits, ParseOptions};
+/// let options = ParseOptions {
+///     limits: Limits {
+///         max_frames: 1000,
+///         ..Default::default()
+///     },
+///     ..Default::default()
+/// };
+/// assert!(parse_frame_sequence_with_options("1-1000", &options).is_ok());
+/// assert!(parse_frame_sequence_with_options("1-1001", &options).is_err());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Limits {
+    /// The maximum length of the input, in bytes.
+    pub max_input_length: usize,
+    /// The maximum number of parts, including the parts of groups and
+    /// exclusions.
+    pub max_parts: usize,
+    /// The maximum number of frames of a sequence or group.
+    ///
+    /// Counted before duplicates and excluded frames are removed and
+    /// including repetitions from holds. The frames of a group are counted
+    /// before e.g. its step size picks from them.
+    pub max_frames: usize,
+    /// The maximum absolute value of any frame.
+    pub max_abs_frame: usize,
+}
+
+impl Limits {
+    /// No limits.
+    pub const NONE: Limits = Limits {
+        max_input_length: usize::MAX,
+        max_parts: usize::MAX,
+        max_frames: usize::MAX,
+        max_abs_frame: usize::MAX,
+    };
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_input_length: 64 * 1024,
+            max_parts: 10_000,
+            max_frames: 10_000_000,
+            max_abs_frame: usize::MAX,
+        }
+    }
+}
+
+pub(crate) fn check_input_length(input: &str, limits: &Limits) -> Result<(), FrameSequenceError> {
+    if limits.max_input_length < input.len() {
+        return Err(FrameSequenceError::new(
+            FrameSequenceError::LimitExceeded,
+            Error::new_from_pos(
+                ErrorVariant::CustomError {
+                    message: format!(
+                        "input is longer than the maximum of {} bytes",
+                        limits.max_input_length
+                    ),
+                },
+                Position::from_start(input),
+            ),
+        )
+        .with_suggestion("raise `Limits::max_input_length`"));
+    }
+    Ok(())
+}
+
+/// Checks the number of parts and frames of a parsed sequence without
+/// expanding it.
+pub(crate) fn check_sequence(
+    input: &str,
+    pairs: Pairs<Rule>,
+    options: &ParseOptions,
+) -> Result<(), FrameSequenceError> {
+    check_part_count(input, pairs.clone(), options)?;
+    check_frame_count(
+        count_frames(pairs, options)?,
+        Span::new(input, 0, input.len()).unwrap(),
+        options,
+    )?;
+
+    Ok(())
+}
+
+pub(crate) fn check_part_count(
+    input: &str,
+    pairs: Pairs<Rule>,
+    options: &ParseOptions,
+) -> Result<(), FrameSequenceError> {
+    let parts = count_parts(pairs);
+    if options.limits.max_parts < parts {
+        return Err(custom_error(
+            FrameSequenceError::LimitExceeded,
+            format!(
+                "sequence has {parts} parts, more than the maximum of {}",
+                options.limits.max_parts
+            ),
+            Span::new(input, 0, input.len()).unwrap(),
+        )
+        .with_suggestion("raise `Limits::max_parts`"));
+    }
+    Ok(())
+}
+
+pub(crate) fn check_frame_count(
+    count: u128,
+    span: Span,
+    options: &ParseOptions,
+) -> Result<u128, FrameSequenceError> {
+    if (options.limits.max_frames as u128) < count {
+        Err(frame_count_error(span, options))
+    } else {
+        Ok(count)
+    }
+}
+
+fn frame_count_error(span: Span, options: &ParseOptions) -> FrameSequenceError {
+    custom_error(
+        FrameSequenceError::LimitExceeded,
+        format!(
+            "expands to more than the maximum of {} frames",
+            options.limits.max_frames
+        ),
+        span,
+    )
+    .with_suggestion("raise `Limits::max_frames`")
+}
+
+pub(crate) fn run_count_error(span: Span, options: &ParseOptions) -> FrameSequenceError {
+    custom_error(
+        FrameSequenceError::LimitExceeded,
+        format!(
+            "splits into more than the maximum of {} runs",
+            options.limits.max_frames
+        ),
+        span,
+    )
+    .with_suggestion("raise `Limits::max_frames`, which also limits the number of runs")
+}
+
+pub(crate) fn check_frame(
+    frame: isize,
+    span: Span,
+    options: &ParseOptions,
+) -> Result<isize, FrameSequenceError> {
+    if options.limits.max_abs_frame < frame.unsigned_abs() {
+        Err(custom_error(
+            FrameSequenceError::LimitExceeded,
+            format!(
+                "frame `{frame}` is larger than the maximum absolute frame of {}",
+                options.limits.max_abs_frame
+            ),
+            span,
+        )
+        .with_suggestion("raise `Limits::max_abs_frame`"))
+    } else {
+        Ok(frame)
+    }
+}
+
+fn count_parts(pairs: Pairs<Rule>) -> usize {
+    pairs
+        .map(|pair| match pair.as_rule() {
+            Rule::FrameSequencePart => 1 + count_parts(pair.into_inner()),
+            Rule::FrameSequenceString | Rule::FrameSequence | Rule::Exclusion | Rule::Group => {
+                count_parts(pair.into_inner())
+            }
+            Rule::SubframeSequencePart => 1,
+            Rule::SubframeSequenceString | Rule::SubframeSequence | Rule::SubframeExclusion => {
+                count_parts(pair.into_inner())
+            }
+            rule if is_dialect_sequence_rule(rule) => pair
+                .into_inner()
+                .filter(|pair| Rule::EOI != pair.as_rule())
+                .count(),
+            _ => 0,
+        })
+        .sum()
+}
+
+/// An upper bound of the number of frames a sequence expands to. Also
+/// checks the start and end of all ranges against
+/// [`max_abs_frame`](Limits::max_abs_frame).
+fn count_frames(pairs: Pairs<Rule>, options: &ParseOptions) -> Result<u128, FrameSequenceError> {
+    pairs.into_iter().try_fold(0u128, |total, pair| {
+        let count = match pair.as_rule() {
+            Rule::Exclusion => count_frames(pair.into_inner(), options)?,
+            rule if is_sequence_rule(rule) => count_frames(pair.into_inner(), options)?,
+            rule if is_range_rule(rule) => {
+                let span = pair.as_span();
+                let mut pairs = pair.clone().into_inner();
+                let left = check_frame(
+                    range_bound(pairs.next().unwrap(), span, options)?,
+                    span,
+                    options,
+                )?;
+                let right = check_frame(
+                    range_bound(pairs.next().unwrap(), span, options)?,
+                    span,
+                    options,
+                )?;
+                let include_last = include_last_frame(&pairs, options);
+                let count = range_modifier_frame_count(
+                    left.abs_diff(right) as u128 + 1,
+                    pairs.find(is_range_modifier),
+                    include_last,
+                    options,
+                )?;
+                part_modifiers_frame_count(&pair, count)?
+            }
+            Rule::Group => {
+                let mut pairs = pair.clone().into_inner();
+                // The frames of the group are generated before the modifier
+                // picks from them.
+                let count = check_frame_count(
+                    count_frames(pairs.next().unwrap().into_inner(), options)?,
+                    pair.as_span(),
+                    options,
+                )?;
+                let include_last = include_last_frame(&pairs, options);
+                let count = range_modifier_frame_count(
+                    count,
+                    pairs.find(is_range_modifier),
+                    include_last,
+                    options,
+                )?;
+                part_modifiers_frame_count(&pair, count)?
+            }
+            Rule::Frame | Rule::Expression => {
+                let span = pair.as_span();
+                check_frame(frame_to_number(pair, options)?, span, options)?;
+                1
+            }
+            _ => 0,
+        };
+        Ok(total.saturating_add(count))
+    })
+}
+
+/// An upper bound of the number of frames the modifier picks from `count`
+/// frames.
+fn range_modifier_frame_count(
+    count: u128,
+    modifier: Option<Pair<Rule>>,
+    include_last: bool,
+    options: &ParseOptions,
+) -> Result<u128, FrameSequenceError> {
+    let stepped = |step: usize| {
+        if 0 == count {
+            0
+        } else {
+            (count - 1) / step as u128 + 1
+        }
+    };
+
+    let frames = match modifier {
+        Some(pair) => match pair.as_rule() {
+            Rule::PositiveNumber | Rule::Product => stepped(step_to_number(pair, options)?),
+            Rule::PassList => pair
+                .into_inner()
+                .map(|step| Ok(stepped(step_to_number(step, options)?)))
+                .sum::<Result<u128, FrameSequenceError>>()?,
+            Rule::FrameCount => {
+                let frame_count = frame_to_number(pair.into_inner().next().unwrap(), options)?;
+                count.min(frame_count.max(0) as u128)
+            }
+            _ => count,
+        },
+        None => count,
+    };
+
+    Ok(frames + include_last as u128)
+}
+
+/// Applies a ping-pong and hold to the number of frames of a range or group.
+fn part_modifiers_frame_count(pair: &Pair<Rule>, count: u128) -> Result<u128, FrameSequenceError> {
+    let count = if pair
+        .clone()
+        .into_inner()
+        .any(|pair| Rule::PingPongSymbol == pair.as_rule())
+    {
+        count.saturating_mul(2)
+    } else {
+        count
+    };
+
+    Ok(count.saturating_mul(hold(pair)? as u128))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse_frame_sequence, parse_frame_sequence_with_options, Limits, ParseOptions};
+
+    #[test]
+    fn test_default_limits() {
+        let error = parse_frame_sequence("1-9999999999").unwrap_err();
+        assert!(error.to_string().contains("maximum of 10000000 frames"));
+        assert!(parse_frame_sequence("1-5000000,!1-5000001").is_err());
+        assert!(parse_frame_sequence("(1..1000)*10001").is_err());
+        assert!(parse_frame_sequence(&"1,".repeat(10_001)).is_err());
+        assert!(parse_frame_sequence(&" ".repeat(64 * 1024 + 1)).is_err());
+    }
+
+    #[test]
+    fn test_custom_limits() {
+        let options = ParseOptions {
+            limits: Limits {
+                max_parts: 2,
+                max_frames: 10,
+                max_abs_frame: 100,
+                ..Limits::NONE
+            },
+            ..Default::default()
+        };
+        let parse = |input| parse_frame_sequence_with_options(input, &options);
+
+        assert_eq!(
+            [1, 3, 5, 7, 9, 11, 13, 15, 17, 19],
+            parse("1-20@2").unwrap().as_slice()
+        );
+        assert!(parse("1-20@2e").is_err());
+        assert!(parse("1-5@pp*2").is_err());
+        assert!(parse("1-10@{1,3}").is_err());
+        assert!(parse("(1-100)#10").is_err());
+        assert!(parse("1-100#10").is_ok());
+
+        assert!(parse("1,2,3").is_err());
+        assert!(parse("(1,2),3").is_err());
+
+        assert!(parse("-100,100").is_ok());
+        assert!(parse("101").is_err());
+        assert!(parse("1-101@10").is_err());
+        assert!(parse("1-10+91").is_err());
+    }
+}
@@ -0,0 +1,264 @@
+use std::ops::Range;
+
+/// The result of [`normalize_frame_sequence()`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct NormalizedInput {
+    /// The normalized frame sequence string.
+    pub input: String,
+    /// What was changed, in the order of the original input.
+    pub changes: Vec<NormalizationChange>,
+}
+
+/// A change made by [`normalize_frame_sequence()`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NormalizationChange {
+    /// What kind of change this is.
+    pub kind: NormalizationKind,
+    /// The byte range of the original input that was changed.
+    pub span: Range<usize>,
+    /// What the range was replaced with. Empty if it was removed.
+    pub replacement: String,
+}
+
+/// The kinds of [`NormalizationChange`]s.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NormalizationKind {
+    /// Whitespace, including non-breaking spaces, was removed or, between
+    /// two frames, replaced with a `,`.
+    Whitespace,
+    /// A `;` was replaced with a `,`, or removed if it ended an empty part.
+    Separator,
+    /// An en dash, em dash or minus sign was replaced with a `-`.
+    Dash,
+    /// A leading, trailing or repeated `,` was removed.
+    EmptyPart,
+}
+
+/// Normalizes a frame sequence string copied from a spreadsheet, chat or
+/// email:
+///
+/// `1, 3; 5 – 10,` ⟶ `1,3,5-10`
+///
+/// Only characters and commas that are never valid in a frame sequence
+/// string are touched, so a valid string is returned unchanged. Whitespace
+/// that could either separate two parts or be inside a part, e.g. in
+/// `1 -5`, is kept, so the string stays invalid.
+///
+/// ```
+/// # use frame_sequence::{normalize_frame_sequence, NormalizationKind};
+/// let normalized = normalize_frame_sequence("1–10;\u{a0}20 ,");
+/// assert_eq!("1-10,20", normalized.input);
+/// assert_eq!(
+///     [
+///         NormalizationKind::Dash,
+///         NormalizationKind::Separator,
+///         NormalizationKind::Whitespace,
+///         NormalizationKind::Whitespace,
+///         NormalizationKind::EmptyPart,
+///     ],
+///     normalized
+///         .changes
+///         .iter()
+///         .map(|change| change.kind)
+///         .collect::<Vec<_>>()
+///         .as_slice()
+/// );
+/// ```
+pub fn normalize_frame_sequence(input: &str) -> NormalizedInput {
+    let mut changes = Vec::new();
+
+    // Replace look-alike characters.
+    let characters = input
+        .char_indices()
+        .map(|(index, character)| {
+            let span = index..index + character.len_utf8();
+            let (replacement, kind) = match character {
+                ';' => (',', NormalizationKind::Separator),
+                '\u{2010}'..='\u{2015}' | '\u{2212}' => ('-', NormalizationKind::Dash),
+                ' ' => return (span, ' '),
+                character if character.is_whitespace() => (' ', NormalizationKind::Whitespace),
+                character => return (span, character),
+            };
+            // Whitespace is reported once it is clear what it becomes.
+            if NormalizationKind::Whitespace != kind {
+                changes.push(NormalizationChange {
+                    kind,
+                    span: span.clone(),
+                    replacement: replacement.to_string(),
+                });
+            }
+            (span, replacement)
+        })
+        .collect::<Vec<_>>();
+
+    let characters = normalize_whitespace(characters, &mut changes);
+    let characters = remove_empty_parts(characters, &mut changes);
+
+    changes.sort_by_key(|change| change.span.start);
+
+    NormalizedInput {
+        input: characters
+            .into_iter()
+            .map(|(_, character)| character)
+            .collect(),
+        changes,
+    }
+}
+
+type Characters = Vec<(Range<usize>, char)>;
+
+/// Removes whitespace or replaces it with a `,` between two frames.
+fn normalize_whitespace(
+    characters: Characters,
+    changes: &mut Vec<NormalizationChange>,
+) -> Characters {
+    let is_part_end = |character: char| character.is_ascii_alphanumeric() || ')' == character;
+    let is_part_start = |character: char| character.is_ascii_alphanumeric() || '(' == character;
+    let is_sign = |character: char| '-' == character || '+' == character;
+
+    let mut result = Characters::new();
+    let mut index = 0;
+    while index < characters.len() {
+        if ' ' != characters[index].1 {
+            result.push(characters[index].clone());
+            index += 1;
+            continue;
+        }
+
+        let start = index;
+        while index < characters.len() && ' ' == characters[index].1 {
+            index += 1;
+        }
+        let span = characters[start].0.start..characters[index - 1].0.end;
+        let previous = result.last().map(|(_, character)| *character);
+        let next = characters.get(index).map(|(_, character)| *character);
+        let after_next = characters.get(index + 1).map(|(_, character)| *character);
+
+        let replacement = match (previous, next) {
+            (Some(previous), Some(next)) if is_part_end(previous) && is_part_start(next) => {
+                Some(",")
+            }
+            // `1 -5` could be `1,-5` or `1-5`.
+            (Some(previous), Some(next))
+                if is_part_end(previous) && is_sign(next) && Some(' ') != after_next =>
+            {
+                None
+            }
+            _ => Some(""),
+        };
+
+        match replacement {
+            Some(replacement) => {
+                if let Some(character) = replacement.chars().next() {
+                    result.push((span.clone(), character));
+                }
+                changes.push(NormalizationChange {
+                    kind: NormalizationKind::Whitespace,
+                    span,
+                    replacement: replacement.to_string(),
+                });
+            }
+            None => result.extend_from_slice(&characters[start..index]),
+        }
+    }
+
+    result
+}
+
+/// Removes leading, trailing and repeated commas.
+fn remove_empty_parts(
+    characters: Characters,
+    changes: &mut Vec<NormalizationChange>,
+) -> Characters {
+    let mut result = Characters::new();
+    for (index, (span, character)) in characters.iter().enumerate() {
+        // The next character that is not a comma, as all commas before it
+        // but this one are removed.
+        let next = characters[index + 1..]
+            .iter()
+            .find(|(_, character)| ',' != *character);
+        let is_empty_part = ',' == *character
+            && match (result.last(), next) {
+                (None, _) | (_, None) => true,
+                (Some((_, previous)), Some((_, next))) => {
+                    matches!(previous, ',' | '(' | '{') || matches!(next, ')' | '}')
+                }
+            };
+
+        if is_empty_part {
+            // A `;` or whitespace that became this comma is removed instead.
+            match changes.iter_mut().find(|change| change.span == *span) {
+                Some(change) => change.replacement.clear(),
+                None => changes.push(NormalizationChange {
+                    kind: NormalizationKind::EmptyPart,
+                    span: span.clone(),
+                    replacement: String::new(),
+                }),
+            }
+        } else {
+            result.push((span.clone(), *character));
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{normalize_frame_sequence, parse_frame_sequence_with_options, ParseOptions};
+
+    #[test]
+    fn test_normalize() {
+        for (input, expected) in [
+            ("1, 2 ,3", "1,2,3"),
+            ("1 2 3", "1,2,3"),
+            ("1;2;;3;", "1,2,3"),
+            ("10 – 20, 30—40 @ 2", "10-20,30-40@2"),
+            ("\u{a0}1-10\t", "1-10"),
+            (",1,,(2, 3,),{", "1,(2,3),{"),
+            ("1,2,,", "1,2"),
+            ("1;2;;", "1,2"),
+            ("1-3 ,,", "1-3"),
+            ("(1,2,,)", "(1,2)"),
+            ("1,,,2", "1,2"),
+            ("1 -5", "1 -5"),
+            ("1 - 5", "1-5"),
+            ("first - last", "first-last"),
+            ("1-10@2,!5", "1-10@2,!5"),
+        ] {
+            assert_eq!(expected, normalize_frame_sequence(input).input, "{input:?}");
+        }
+    }
+
+    #[test]
+    fn test_normalize_changes() {
+        let normalized = normalize_frame_sequence("1 ;2");
+        assert_eq!("1,2", normalized.input);
+        assert_eq!(
+            [(1..2, ""), (2..3, ",")],
+            normalized
+                .changes
+                .iter()
+                .map(|change| (change.span.clone(), change.replacement.as_str()))
+                .collect::<Vec<_>>()
+                .as_slice()
+        );
+        assert!(normalize_frame_sequence("1-10@2").changes.is_empty());
+    }
+
+    #[test]
+    fn test_parse_normalized() {
+        let options = ParseOptions {
+            normalize: true,
+            ..Default::default()
+        };
+        let frames = parse_frame_sequence_with_options("1 – 3; 10,", &options).unwrap();
+        assert_eq!([1, 2, 3, 10], frames.as_slice());
+        // The error refers to the original input.
+        let error = parse_frame_sequence_with_options("1 -5", &options).unwrap_err();
+        assert_eq!(1..1, error.span());
+        assert!(parse_frame_sequence_with_options("1, 2", &ParseOptions::default()).is_err());
+        // A range in Houdini, not three frames.
+        let error = parse_frame_sequence_with_options("1 100 5", &options).unwrap_err();
+        assert!(error.suggestion().unwrap().contains("Dialect::Houdini"));
+    }
+}
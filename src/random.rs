@@ -0,0 +1,57 @@
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+};
+
+/// A [SplitMix64](https://prng.di.unimi.it/splitmix64.c) pseudo random
+/// number generator.
+///
+/// The sequence of numbers for a given seed is part of this crate's
+/// contract and must never change.
+pub(crate) struct SplitMix64(u64);
+
+impl SplitMix64 {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    /// Seeds the generator from the per-process random keys of the standard
+    /// library's [`RandomState`].
+    pub(crate) fn from_entropy() -> Self {
+        Self(RandomState::new().build_hasher().finish())
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number in `0..bound`.
+    fn next_below(&mut self, bound: u64) -> u64 {
+        ((self.next_u64() as u128 * bound as u128) >> 64) as _
+    }
+
+    /// Fisher–Yates shuffle.
+    pub(crate) fn shuffle<T>(&mut self, elements: &mut [T]) {
+        (1..elements.len()).rev().for_each(|i| {
+            let j = self.next_below(i as u64 + 1) as usize;
+            elements.swap(i, j);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SplitMix64;
+
+    #[test]
+    fn test_split_mix_64() {
+        // Reference values from the C implementation.
+        let mut rng = SplitMix64::new(0);
+        assert_eq!(0xe220a8397b1dcdaf, rng.next_u64());
+        assert_eq!(0x6e789e6aa1b965f4, rng.next_u64());
+    }
+}
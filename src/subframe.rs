@@ -0,0 +1,330 @@
+use crate::{
+    custom_error,
+    limits::{check_frame_count, check_input_length, check_part_count},
+    remove_duplicates, FrameSequenceError, FrameSequenceParser, ParseOptions, Rule,
+};
+use itertools::Itertools;
+use pest::{
+    error::{Error, ErrorVariant},
+    iterators::{Pair, Pairs},
+    Parser, Span,
+};
+use std::{cmp::Ordering, collections::HashSet, iter::successors};
+
+/// The maximum number of decimal places a subframe may have.
+const MAX_DECIMAL_PLACES: usize = 18;
+
+/// Parse a frame sequence string with fractional frames and step sizes into
+/// a [`Vec`]`<`[`f64`]`>` of frames.
+///
+/// All arithmetic is done on exact fixed-point numbers, so the resulting
+/// frames are the closest [`f64`] to the decimal value they represent:
+///
+/// `1-2@0.25` ⟶ `[1.0, 1.25, 1.5, 1.75, 2.0]`
+///
+/// Ranges, reverse ranges, the `..` and `:` range separators and
+/// exclusions work as in [`parse_frame_sequence()`](crate::parse_frame_sequence).
+/// Binary splitting is not supported.
+///
+/// The default [`Limits`](crate::Limits) apply.
+pub fn parse_subframe_sequence(input: &str) -> Result<Vec<f64>, FrameSequenceError> {
+    parse_subframe_sequence_with_options(input, &ParseOptions::default())
+}
+
+/// Parse a frame sequence string with fractional frames and step sizes into
+/// a [`Vec`]`<`[`f64`]`>` of frames with the given [`ParseOptions`].
+///
+/// Only the [`limits`](ParseOptions::limits) are used. A subframe counts
+/// as one frame and its integer part must not exceed
+/// [`max_abs_frame`](crate::Limits::max_abs_frame).
+///
+/// ```
+/// # use frame_sequence::{parse_subframe_sequence_with_options, Limits, ParseOptions};
+/// let options = ParseOptions {
+///     limits: Limits {
+///         max_frames: 4,
+///         ..Default::default()
+///     },
+///     ..Default::default()
+/// };
+/// assert!(parse_subframe_sequence_with_options("1-2@0.5", &options).is_ok());
+/// assert!(parse_subframe_sequence_with_options("1-2@0.25", &options).is_err());
+/// ```
+pub fn parse_subframe_sequence_with_options(
+    input: &str,
+    options: &ParseOptions,
+) -> Result<Vec<f64>, FrameSequenceError> {
+    check_input_length(input, &options.limits)?;
+    let token_tree = FrameSequenceParser::parse(Rule::SubframeSequenceString, input)?;
+
+    let decimal_places = token_tree
+        .clone()
+        .flatten()
+        .filter(|pair| pair.as_rule() == Rule::PositiveSubframe)
+        .map(|pair| decimal_places(&pair))
+        .max()
+        .unwrap_or(0);
+
+    if MAX_DECIMAL_PLACES < decimal_places {
+        return Err(FrameSequenceError::new(
+            FrameSequenceError::LimitExceeded,
+            Error::new_from_pos(
+                ErrorVariant::CustomError {
+                    message: format!(
+                        "subframes can have at most {MAX_DECIMAL_PLACES} decimal places"
+                    ),
+                },
+                token_tree.peek().unwrap().as_span().start_pos(),
+            ),
+        ));
+    }
+
+    check_part_count(input, token_tree.clone(), options)?;
+    check_frame_count(
+        count_subframes(token_tree.clone(), decimal_places, options)?,
+        Span::new(input, 0, input.len()).unwrap(),
+        options,
+    )?;
+
+    let excluded = excluded_subframes(token_tree.clone(), decimal_places)?
+        .into_iter()
+        .collect::<HashSet<_>>();
+
+    let scale = 10i128.pow(decimal_places as _) as f64;
+
+    Ok(remove_duplicates(subframe_sequence_token_tree_to_frames(
+        token_tree,
+        decimal_places,
+    )?)
+    .into_iter()
+    .filter(|frame| !excluded.contains(frame))
+    .map(|frame| frame as f64 / scale)
+    .collect())
+}
+
+fn decimal_places(subframe: &Pair<Rule>) -> usize {
+    subframe
+        .as_str()
+        .split_once('.')
+        .map_or(0, |(_, fraction)| fraction.len())
+}
+
+/// Converts a subframe to a fixed-point number with `decimal_places`.
+fn subframe_to_fixed_point(
+    subframe: Pair<Rule>,
+    decimal_places: usize,
+) -> Result<i128, FrameSequenceError> {
+    let (integer, fraction) = subframe
+        .as_str()
+        .split_once('.')
+        .unwrap_or((subframe.as_str(), ""));
+
+    format!("{integer}{fraction:0<decimal_places$}")
+        .parse::<i128>()
+        .map_err(|_| {
+            custom_error(
+                FrameSequenceError::Overflow,
+                format!("subframe `{}` is out of range", subframe.as_str()),
+                subframe.as_span(),
+            )
+        })
+}
+
+/// The start, end and step size of a subframe range.
+fn subframe_range(
+    range: Pair<Rule>,
+    decimal_places: usize,
+) -> Result<(i128, i128, i128), FrameSequenceError> {
+    let mut pairs = range.into_inner();
+    let left = subframe_to_fixed_point(pairs.next().unwrap(), decimal_places)?;
+    let right = subframe_to_fixed_point(pairs.next().unwrap(), decimal_places)?;
+
+    // Do we have an `@`?
+    let step = if pairs.next().is_some() {
+        let pair = pairs.next().unwrap();
+        let span = pair.as_span();
+        let step = subframe_to_fixed_point(pair, decimal_places)?;
+        if 0 == step {
+            return Err(custom_error(
+                FrameSequenceError::ZeroStep,
+                "step size must be greater than zero".to_string(),
+                span,
+            )
+            .with_suggestion("use a step size greater than `0`"));
+        }
+        step
+    } else {
+        10i128.pow(decimal_places as _)
+    };
+
+    Ok((left, right, step))
+}
+
+/// The number of subframes a sequence expands to, without expanding it.
+/// Also checks all subframes against
+/// [`max_abs_frame`](crate::Limits::max_abs_frame).
+fn count_subframes(
+    pairs: Pairs<Rule>,
+    decimal_places: usize,
+    options: &ParseOptions,
+) -> Result<u128, FrameSequenceError> {
+    pairs.into_iter().try_fold(0u128, |total, pair| {
+        let count = match pair.as_rule() {
+            Rule::SubframeSequenceString
+            | Rule::SubframeSequence
+            | Rule::SubframeSequencePart
+            | Rule::SubframeExclusion => {
+                count_subframes(pair.into_inner(), decimal_places, options)?
+            }
+            Rule::SubframeRange => {
+                for subframe in pair.clone().into_inner().take(2) {
+                    check_subframe(subframe, decimal_places, options)?;
+                }
+                let (left, right, step) = subframe_range(pair, decimal_places)?;
+                left.abs_diff(right) / step.unsigned_abs() + 1
+            }
+            Rule::Subframe => {
+                check_subframe(pair, decimal_places, options)?;
+                1
+            }
+            _ => 0,
+        };
+        Ok(total.saturating_add(count))
+    })
+}
+
+fn check_subframe(
+    subframe: Pair<Rule>,
+    decimal_places: usize,
+    options: &ParseOptions,
+) -> Result<(), FrameSequenceError> {
+    let span = subframe.as_span();
+    let frame = subframe_to_fixed_point(subframe, decimal_places)?.unsigned_abs()
+        / 10u128.pow(decimal_places as _);
+    if (options.limits.max_abs_frame as u128) < frame {
+        Err(custom_error(
+            FrameSequenceError::LimitExceeded,
+            format!(
+                "subframe `{}` is larger than the maximum absolute frame of {}",
+                span.as_str(),
+                options.limits.max_abs_frame
+            ),
+            span,
+        )
+        .with_suggestion("raise `Limits::max_abs_frame`"))
+    } else {
+        Ok(())
+    }
+}
+
+fn subframe_sequence_token_tree_to_frames(
+    pairs: Pairs<Rule>,
+    decimal_places: usize,
+) -> Result<Vec<i128>, FrameSequenceError> {
+    pairs
+        .into_iter()
+        .map(|pair| {
+            Ok(match pair.as_rule() {
+                Rule::SubframeSequenceString
+                | Rule::SubframeSequence
+                | Rule::SubframeSequencePart => {
+                    subframe_sequence_token_tree_to_frames(pair.into_inner(), decimal_places)?
+                }
+                Rule::SubframeRange => {
+                    let (left, right, step) = subframe_range(pair, decimal_places)?;
+                    match left.cmp(&right) {
+                        Ordering::Less => successors(Some(left), |frame| frame.checked_add(step))
+                            .take_while(|frame| *frame <= right)
+                            .collect(),
+                        Ordering::Greater => {
+                            successors(Some(left), |frame| frame.checked_sub(step))
+                                .take_while(|frame| right <= *frame)
+                                .collect()
+                        }
+                        Ordering::Equal => vec![left],
+                    }
+                }
+                Rule::Subframe => vec![subframe_to_fixed_point(pair, decimal_places)?],
+                _ => vec![],
+            })
+        })
+        .flatten_ok()
+        .collect()
+}
+
+fn excluded_subframes(
+    pairs: Pairs<Rule>,
+    decimal_places: usize,
+) -> Result<Vec<i128>, FrameSequenceError> {
+    pairs
+        .into_iter()
+        .map(|pair| match pair.as_rule() {
+            Rule::SubframeSequenceString | Rule::SubframeSequence => {
+                excluded_subframes(pair.into_inner(), decimal_places)
+            }
+            Rule::SubframeExclusion => {
+                subframe_sequence_token_tree_to_frames(pair.into_inner(), decimal_places)
+            }
+            _ => Ok(vec![]),
+        })
+        .flatten_ok()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_subframe_sequence() {
+        use crate::parse_subframe_sequence;
+        let frames = parse_subframe_sequence("1-2@0.25").unwrap();
+        assert_eq!([1.0, 1.25, 1.5, 1.75, 2.0], frames.as_slice());
+    }
+
+    #[test]
+    fn test_subframe_sequence_reversed() {
+        use crate::parse_subframe_sequence;
+        let frames = parse_subframe_sequence("2-1@.3,1.1,1.4").unwrap();
+        assert_eq!([2.0, 1.7, 1.4, 1.1], frames.as_slice());
+    }
+
+    #[test]
+    fn test_subframe_sequence_negative() {
+        use crate::parse_subframe_sequence;
+        let frames = parse_subframe_sequence("-1.5..0.5,!-0.5").unwrap();
+        assert_eq!([-1.5, 0.5], frames.as_slice());
+    }
+
+    #[test]
+    fn test_subframe_zero_step() {
+        use crate::parse_subframe_sequence;
+        assert!(parse_subframe_sequence("1-2@0.0").is_err());
+    }
+
+    #[test]
+    fn test_subframe_limits() {
+        use crate::{
+            parse_subframe_sequence, parse_subframe_sequence_with_options, FrameSequenceError,
+            Limits, ParseOptions,
+        };
+        assert!(matches!(
+            parse_subframe_sequence("0-100000000000"),
+            Err(FrameSequenceError::LimitExceeded(_))
+        ));
+        assert!(matches!(
+            parse_subframe_sequence("0-1@0.000000000000000001"),
+            Err(FrameSequenceError::LimitExceeded(_))
+        ));
+        let options = ParseOptions {
+            limits: Limits {
+                max_parts: 2,
+                max_abs_frame: 10,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let frames = parse_subframe_sequence_with_options("-10.5-10@5,!0", &options).unwrap();
+        assert_eq!([-10.5, -5.5, -0.5, 4.5, 9.5], frames.as_slice());
+        assert!(parse_subframe_sequence_with_options("1,2,3", &options).is_err());
+        assert!(parse_subframe_sequence_with_options("11.5", &options).is_err());
+    }
+}